@@ -13,6 +13,9 @@ criterion_group!(
     expressions::unary_chain_spaced,
     expressions::obj_initialiser,
     expressions::obj_property_access,
-    function::many_args
+    function::many_args,
+    function::many_calls,
+    function::recursive,
+    function::when_chain
 );
 criterion_main!(benches);
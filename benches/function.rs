@@ -13,3 +13,63 @@ some_calculation(1, 2, 3, 4, 5, 6, 7)!
         })
     });
 }
+
+pub fn many_calls(c: &mut Criterion) {
+    c.bench_function("many_calls", |b| {
+        b.iter(|| {
+            Interpreter::new_eval(black_box(
+                r#"
+function work(a, b) => {
+   var var c = a + b!
+   c = c * 2!
+   return c!
+}!
+var var i = 0!
+when i < 100 {
+   var var result = work(i, 1)!
+   i = i + 1!
+}
+i = 1!
+        "#,
+            ))
+        })
+    });
+}
+
+pub fn recursive(c: &mut Criterion) {
+    c.bench_function("recursive", |b| {
+        b.iter(|| {
+            Interpreter::new_eval(black_box(
+                r#"
+function fib(n) => {
+   if n < 2 {
+      return n!
+   }
+   var var a = fib(n - 1)!
+   var var b = fib(n - 2)!
+   return a + b!
+}!
+var const result = fib(12)!
+        "#,
+            ))
+        })
+    });
+}
+
+pub fn when_chain(c: &mut Criterion) {
+    c.bench_function("when_chain", |b| {
+        b.iter(|| {
+            Interpreter::new_eval(black_box(
+                r#"
+var var i = 0!
+var var total = 0!
+when i < 200 {
+   total = total + i!
+   i = i + 1!
+}
+i = 1!
+        "#,
+            ))
+        })
+    });
+}
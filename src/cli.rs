@@ -2,28 +2,58 @@ use std::{fs, path::PathBuf};
 
 use anyhow::{Context, Result};
 use clap::Parser;
-use dreamberd_noodles_interpreter::interpreter::{runtime::value::Value, Interpreter};
+use dreamberd_noodles_interpreter::interpreter::{
+    error::Error,
+    runtime::{
+        store::{FileStore, MemoryStore},
+        value::Value,
+    },
+    Interpreter,
+};
 use rustyline::{error::ReadlineError, DefaultEditor};
 
 #[derive(Parser)]
 #[command(author, about, version)]
 pub struct Cli {
-    /// The path to the script file to use
-    file: Option<PathBuf>,
+    /// The paths to the script files to use
+    /// - They are evaluated in order, each as its own file that can be exported to by its file name
+    files: Vec<PathBuf>,
+
+    /// A directory to keep `const const const` values in, instead of the one in the user's data directory
+    #[arg(long)]
+    store: Option<PathBuf>,
+
+    /// Keeps `const const const` values in memory, so they aren't shared with other processes
+    #[arg(long, conflicts_with = "store")]
+    memory_store: bool,
 }
 
 impl Cli {
     /// Process the CLI arguments and directly runs the interpreter
     pub fn process_from_cli(self) -> Result<()> {
-        if let Some(file) = self.file {
-            let file = fs::read_to_string(&file)
-                .with_context(|| format!("Failed to read file at `{}`", file.display()))?;
-            Interpreter::new_eval(&file)?;
+        let interpreter = Interpreter::new();
+        if let Some(store) = self.store {
+            interpreter.set_store(FileStore::new(store));
+        } else if self.memory_store {
+            interpreter.set_store(MemoryStore::new());
+        }
+
+        if !self.files.is_empty() {
+            for path in self.files {
+                let code = fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read file at `{}`", path.display()))?;
+                let name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                if let Err(err) = interpreter.eval_file(&name, &code) {
+                    eprintln!("{}", render_error(&err, &code, &path.display().to_string()));
+                    std::process::exit(1);
+                }
+            }
             Ok(())
         } else {
             // repl mode
-            let interpreter = Interpreter::new();
-
             let mut editor = DefaultEditor::new().context("Failed to start repl with history")?;
 
             loop {
@@ -53,7 +83,7 @@ impl Cli {
                                 );
                             }
                             Err(err) => {
-                                eprintln!("{}", err);
+                                eprintln!("{}", render_error(&err, &line, "<repl>"));
                             }
                         }
                     }
@@ -69,3 +99,51 @@ impl Cli {
         }
     }
 }
+
+/// Renders the error similar to how rustc does, pointing at the code where it happened
+fn render_error(err: &Error, code: &str, path: &str) -> String {
+    let (message, line, column, notes) = match err {
+        Error::EvalError { error, trace } => {
+            let Some(trace) = trace else {
+                return format!("error: {error}");
+            };
+            let notes = trace
+                .stack
+                .iter()
+                .rev()
+                .map(|frame| {
+                    format!(
+                        "in function `{}` called at {path}:{}",
+                        frame.name, frame.call_site
+                    )
+                })
+                .collect::<Vec<_>>();
+            (
+                error.to_string(),
+                trace.location.line,
+                trace.location.column,
+                notes,
+            )
+        }
+        Error::Parse {
+            kind, line, column, ..
+        } => (kind.to_string(), *line, *column, Vec::new()),
+    };
+
+    let gutter = " ".repeat(line.to_string().len());
+    let source = code.lines().nth(line - 1).unwrap_or_default();
+    // columns count chars, so tabs and such are kept to line up with the source
+    let padding = source
+        .chars()
+        .take(column - 1)
+        .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+        .collect::<String>();
+
+    let mut out = format!(
+        "error: {message}\n{gutter}--> {path}:{line}:{column}\n{gutter} |\n{line} | {source}\n{gutter} | {padding}^"
+    );
+    for note in notes {
+        out.push_str(&format!("\n{gutter} = note: {note}"));
+    }
+    out
+}
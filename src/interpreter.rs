@@ -7,10 +7,26 @@ use nom::{combinator::eof, sequence::tuple, Parser};
 use self::{
     evaluators::statement::Statement,
     parsers::types::Position,
-    runtime::{state::InterpreterState, stdlib},
-    static_analysis::Analysis,
+    runtime::{
+        budget::{CancelHandle, Limits},
+        clock::Clock,
+        files,
+        heap::HeapStats,
+        io::{Io, IoHost},
+        locale::Locale,
+        realm::Realm,
+        state::{FunctionVariant, InterpreterState},
+        stdlib,
+        store::{Store, StoreHost},
+    },
+    static_analysis::{diagnostics, Analysis},
+};
+use crate::prelude::Wrapper;
+use std::{
+    borrow::Cow,
+    fmt::Debug,
+    sync::{atomic::Ordering, Arc},
 };
-use std::fmt::Debug;
 
 pub mod error;
 mod evaluators;
@@ -34,16 +50,54 @@ impl Debug for Interpreter {
 impl Interpreter {
     /// Evaluate the given code
     /// - This is a synchronous function and will block until the code is finished executing
+    /// - `=====` lines split the code into files, and the code before the first one continues the current file
     pub fn eval(&self, code: &str) -> Result<Vec<Value>, self::error::Error> {
+        self.diagnose(code)?;
+        self.state.budget.reset_steps();
+        self.state.store.lock().unwrap().refresh();
+        // a trace left by an error that was ignored belongs to no error of this evaluation
+        self.state.take_error_trace();
+        self.eval_files(code)
+            .inspect_err(|_| self.state.clear_tasks())
+    }
+
+    /// Evaluate the given code as a new file with the name, which other files can export to
+    /// - Anything declared by previously evaluated files is not visible, except what was exported to it
+    pub fn eval_file(&self, name: &str, code: &str) -> Result<Vec<Value>, self::error::Error> {
+        self.state.enter_file(Some(name));
+        self.eval(code)
+    }
+
+    fn eval_files(&self, code: &str) -> Result<Vec<Value>, self::error::Error> {
+        let mut values = Vec::new();
+        for (i, file) in files::split(code).into_iter().enumerate() {
+            if i > 0 {
+                self.state.enter_file(file.name);
+            }
+            values.extend(self.eval_code(file.code, file.line)?);
+        }
+        Ok(values)
+    }
+
+    /// # Arguments
+    /// - `line`: line of the whole source code that the code starts at
+    fn eval_code(&self, code: &str, line: usize) -> Result<Vec<Value>, self::error::Error> {
+        let traced = |error| self.traced(error);
+
         // TODO: this is terrible
         let reverse_code = code.lines().rev().collect::<String>();
         let total_lines = code.lines().count();
 
-        let analysis = Analysis::analyze(code);
+        let analysis = Analysis::analyze_at(code, line);
         self.state.add_analysis_info(analysis);
 
         let binding = (self, code);
-        let mut code_with_pos = Position::new_with_extra(code, &binding);
+        let mut code_with_pos = Position {
+            line,
+            ..Position::new_with_extra(code, &binding)
+        };
+        // variables can expire between evaluations
+        self.state.expire_timers(code_with_pos).map_err(traced)?;
 
         let mut values = Vec::new();
 
@@ -52,7 +106,11 @@ impl Interpreter {
             let StatementReturn {
                 value,
                 return_value,
-            } = statement.eval(code_after)?;
+            } = statement
+                .eval_traced(code_with_pos, code_after)
+                .map_err(traced)?;
+            // async function calls take turns with each statement
+            self.state.run_turn(code_with_pos).map_err(traced)?;
 
             // TODO: remove this later maybe too
             // if let Some(new_pos) = new_pos {
@@ -60,6 +118,7 @@ impl Interpreter {
             // }
 
             if let Statement::Return(_) = statement {
+                self.state.run_tasks(code_with_pos).map_err(traced)?;
                 if let Some(return_value) = return_value {
                     values.push(return_value);
                 }
@@ -87,17 +146,34 @@ impl Interpreter {
             code_with_pos = code_after;
         }
 
+        if let Some(error) = diagnostics::leftover(code, line, code_with_pos) {
+            return Err(error);
+        }
+        self.state.run_tasks(code_with_pos).map_err(traced)?;
         Ok(values)
     }
 
     /// Evaluate the given code but for repl
     /// This will first try to parse the code as an expression first
     pub fn eval_repl(&self, code: &str) -> Result<Vec<Value>, self::error::Error> {
+        self.diagnose(code)?;
+        self.state.budget.reset_steps();
+        self.state.store.lock().unwrap().refresh();
+        // a trace left by an error that was ignored belongs to no error of this evaluation
+        self.state.take_error_trace();
+        self.eval_repl_code(code)
+            .inspect_err(|_| self.state.clear_tasks())
+    }
+
+    fn eval_repl_code(&self, code: &str) -> Result<Vec<Value>, self::error::Error> {
+        let traced = |error| self.traced(error);
+
         let analysis = Analysis::analyze(code);
         self.state.add_analysis_info(analysis);
 
         let binding = (self, code);
         let mut code_with_pos = Position::new_with_extra(code, &binding);
+        self.state.expire_timers(code_with_pos).map_err(traced)?;
 
         let mut expr = verify(tuple((Expression::parse, eof)).map(|(expr, _)| expr), |e| {
             // dont allow strings as they could be implicit strings
@@ -113,20 +189,29 @@ impl Interpreter {
             }
         });
         if let Ok((_, expr)) = expr.parse(code_with_pos) {
-            let res: Value = expr.eval(code_with_pos)?.0.into_owned();
+            let res: Value = expr
+                .eval(code_with_pos)
+                .inspect_err(|_| self.state.trace_error(code_with_pos.into()))
+                .map_err(traced)?
+                .0
+                .into_owned();
             return Ok(vec![res]);
         }
 
         let mut values = Vec::new();
 
         while let Ok((code_after, statement)) = Statement::parse(code_with_pos) {
-            code_with_pos = code_after;
             let StatementReturn {
                 value,
                 return_value,
-            } = statement.eval(code_with_pos)?;
+            } = statement
+                .eval_traced(code_with_pos, code_after)
+                .map_err(traced)?;
+            self.state.run_turn(code_with_pos).map_err(traced)?;
+            code_with_pos = code_after;
 
             if let Statement::Return(_) = statement {
+                self.state.run_tasks(code_with_pos).map_err(traced)?;
                 if let Some(return_value) = return_value {
                     values.push(return_value);
                 }
@@ -138,9 +223,29 @@ impl Interpreter {
             }
         }
 
+        if let Some(error) = diagnostics::leftover(code, 1, code_with_pos) {
+            return Err(error);
+        }
+        self.state.run_tasks(code_with_pos).map_err(traced)?;
         Ok(values)
     }
 
+    /// Attaches where the error happened to it
+    fn traced(&self, error: runtime::Error) -> self::error::Error {
+        self::error::Error::EvalError {
+            error,
+            trace: self.state.take_error_trace(),
+        }
+    }
+
+    /// Checks the code for problems if parse diagnostics is enabled
+    fn diagnose(&self, code: &str) -> Result<(), self::error::Error> {
+        if self.state.parse_diagnostics.load(Ordering::Relaxed) {
+            diagnostics::check(code, &self.state.locale.lock().unwrap())?;
+        }
+        Ok(())
+    }
+
     /// Create a new interpreter and evaluate the given code
     /// - This is a synchronous function and will block until the code is finished executing
     pub fn new_eval(code: &str) -> Result<(), self::error::Error> {
@@ -150,10 +255,156 @@ impl Interpreter {
     }
 
     pub fn new() -> Self {
+        Self::new_with_realm(Realm::new())
+    }
+
+    /// Creates a new interpreter that uses the prototypes of the given realm
+    /// - Changes made to the prototypes will be seen by all interpreters sharing the realm
+    pub fn new_with_realm(realm: Realm) -> Self {
         let interpreter = Self {
-            state: InterpreterState::default(),
+            state: InterpreterState::new(realm),
         };
         stdlib::load(&interpreter);
+        interpreter.state.enter_file(None);
         interpreter
     }
+
+    /// Gets the realm of this interpreter, which can be shared with [`Interpreter::new_with_realm`]
+    pub fn realm(&self) -> Realm {
+        self.state.realm.clone()
+    }
+
+    /// Declares a function implemented in rust, the same way as the standard library functions
+    /// # Arg count
+    /// - If you pass `None`, it can accept any number of arguments
+    pub fn register_fn<F>(&self, name: &str, arg_count: Option<usize>, func: F)
+    where
+        F: Fn(&Interpreter, Vec<Wrapper<Cow<Value>>>) -> Result<Value, runtime::Error>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.state
+            .add_func_declare_var(name, FunctionVariant::Native(Arc::new(func)), arg_count);
+    }
+
+    /// Enables checking the code for problems before evaluating, which results in [`Error::Parse`](self::error::Error::Parse)
+    /// - Unbalanced scopes, unterminated block comments and strings, and mismatched quotes are reported
+    /// - Without this, those problems usually silently end up as implicit strings
+    pub fn with_parse_diagnostics(self, enabled: bool) -> Self {
+        self.set_parse_diagnostics(enabled);
+        self
+    }
+
+    /// Sets if the code is checked for problems before evaluating
+    pub fn set_parse_diagnostics(&self, enabled: bool) {
+        self.state
+            .parse_diagnostics
+            .store(enabled, Ordering::Relaxed);
+    }
+
+    /// Enables strict mode, where writing to a constant is an error instead of being ignored
+    /// - Reassigning a `const const` or `const var` is [`Error::ConstAssignment`](runtime::Error::ConstAssignment)
+    /// - Setting a property of a `const const` or `var const` is [`Error::ConstMutation`](runtime::Error::ConstMutation)
+    /// - Deleting them or their properties, and calling methods that change them like `push`, are the same errors
+    pub fn with_strict_mode(self, enabled: bool) -> Self {
+        self.set_strict_mode(enabled);
+        self
+    }
+
+    /// Sets if writing to a constant is an error
+    pub fn set_strict_mode(&self, enabled: bool) {
+        self.state.strict.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Sets how many past values of each variable and property are kept for `previous`
+    pub fn with_history_len(self, len: usize) -> Self {
+        self.set_history_len(len);
+        self
+    }
+
+    /// Sets how many past values of each variable and property are kept for `previous`
+    /// - Values that are already kept beyond the new length are dropped on their next assignment
+    pub fn set_history_len(&self, len: usize) {
+        self.state.history_len.store(len, Ordering::Relaxed);
+    }
+
+    /// Uses the currency symbol of the locale for interpolating strings
+    pub fn with_locale(self, locale: Locale) -> Self {
+        self.set_locale(locale);
+        self
+    }
+
+    /// Replaces the locale, which applies to code that is parsed after this
+    pub fn set_locale(&self, locale: Locale) {
+        *self.state.locale.lock().unwrap() = locale;
+    }
+
+    /// Stops evaluating with an error when any of the limits is exceeded
+    pub fn with_limits(self, limits: Limits) -> Self {
+        self.set_limits(limits);
+        self
+    }
+
+    /// Replaces the limits of evaluating, which is checked before each statement and function call
+    pub fn set_limits(&self, limits: Limits) {
+        *self.state.budget.limits.lock().unwrap() = limits;
+    }
+
+    /// Frees objects that are only kept alive by reference cycles
+    /// - This also happens automatically as more objects are created
+    /// # Returns
+    /// - Number of objects that were freed
+    pub fn collect_garbage(&self) -> usize {
+        self.state.heap.collect()
+    }
+
+    /// Number of objects and properties that are alive
+    pub fn heap_stats(&self) -> HeapStats {
+        self.state.heap.stats()
+    }
+
+    /// Gets a handle that can stop evaluating from another thread
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.state.budget.cancel.clone()
+    }
+
+    /// Uses the given I/O for `print` and `input` instead of stdout and stdin
+    pub fn with_io(self, io: impl Io + 'static) -> Self {
+        self.set_io(io);
+        self
+    }
+
+    /// Replaces the I/O used for `print` and `input`
+    pub fn set_io(&self, io: impl Io + 'static) {
+        *self.state.io.lock().unwrap() = IoHost(Box::new(io));
+    }
+
+    /// Uses the given store for `const const const` values instead of files in the user's data directory
+    pub fn with_store(self, store: impl Store + 'static) -> Self {
+        self.set_store(store);
+        self
+    }
+
+    /// Replaces the store of `const const const` values
+    pub fn set_store(&self, store: impl Store + 'static) {
+        *self.state.store.lock().unwrap() = StoreHost::new(Box::new(store));
+    }
+
+    /// Uses the given clock for lifetimes in seconds and `sleep` instead of the system's clock
+    pub fn with_clock(self, clock: impl Clock + 'static) -> Self {
+        self.set_clock(clock);
+        self
+    }
+
+    /// Replaces the clock, where variables that haven't expired yet keep the time they have left
+    pub fn set_clock(&self, clock: impl Clock + 'static) {
+        self.state.timers.lock().unwrap().set_clock(Box::new(clock));
+    }
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
 }
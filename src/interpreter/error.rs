@@ -1,7 +1,44 @@
 use thiserror::Error;
 
+use super::runtime::error::Trace;
+
 #[derive(Error, Debug)]
 pub enum Error {
-    #[error("Failed to evaluate: {0}")]
-    EvalError(#[from] super::runtime::error::Error),
+    #[error("Failed to evaluate: {error}")]
+    EvalError {
+        error: super::runtime::error::Error,
+        /// Where the error happened, if known
+        trace: Option<Trace>,
+    },
+    #[error("Failed to parse at line {line}, column {column}: {kind}\n{snippet}")]
+    Parse {
+        kind: ParseErrorKind,
+        line: usize,
+        column: usize,
+        /// The line of code where the problem is at
+        snippet: String,
+    },
+}
+
+impl From<super::runtime::error::Error> for Error {
+    fn from(error: super::runtime::error::Error) -> Self {
+        Self::EvalError { error, trace: None }
+    }
+}
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+/// Problems that parse diagnostics reports
+pub enum ParseErrorKind {
+    #[error("unclosed scope, missing `}}`")]
+    UnclosedScope,
+    #[error("unexpected `}}` without a matching `{{`")]
+    UnexpectedScopeEnd,
+    #[error("unterminated block comment, missing `*/`")]
+    UnterminatedBlockComment,
+    #[error("unterminated string")]
+    UnterminatedString,
+    #[error("closing quotes don't match the opening quotes")]
+    MismatchedQuotes,
+    #[error("code that can't be parsed as a statement")]
+    UnparsedStatement,
 }
@@ -1,11 +1,15 @@
 pub mod array;
+pub mod cache;
 pub mod class;
 pub mod conditional;
 mod control_flow;
+mod delete;
 pub mod expression;
+mod files;
 pub mod function;
 mod object;
 pub mod parsers;
 mod scope;
 pub mod statement;
+mod template;
 pub mod variable;
@@ -28,7 +28,11 @@ impl ArrayInitialiser {
         let item = tuple((ws, item, ws)).map(|(_, item, _)| item);
         let items = separated_list0(comma, item);
 
-        let (input, (_, items, _)) = tuple((brace_start, items, brace_end))(input)?;
+        // `[]` would otherwise have an empty implicit string as its item
+        let empty = tuple((complete::char('['), ws, complete::char(']'))).map(|_| Vec::new());
+        let items = tuple((brace_start, items, brace_end)).map(|(_, items, _)| items);
+
+        let (input, items) = alt((empty, items))(input)?;
 
         Ok((input, Self(items)))
     }
@@ -0,0 +1,100 @@
+//! Caches parse results of code that is evaluated more than once, like function and `when` bodies
+
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{Arc, Mutex},
+};
+
+use crate::{interpreter::runtime::state::Lookup, parsers::PosWithInfo};
+
+use super::{expression::Expression, parsers::AstParseResult, statement::Statement};
+
+/// Parse results of a piece of code, keyed by the index where the parse started
+/// - Parsing looks up names in the current scope, so an entry is only reused if those names still resolve the same way
+pub struct ParseCache<T> {
+    entries: Mutex<HashMap<usize, Arc<Entry<T>>>>,
+}
+
+struct Entry<T> {
+    value: Arc<T>,
+    /// Names that were looked up while parsing
+    lookups: Vec<Lookup>,
+    /// Index, line and column of where the parse ended
+    end: (usize, usize, usize),
+}
+
+impl<T> Default for ParseCache<T> {
+    fn default() -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<T> Debug for ParseCache<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ParseCache")
+            .field("entries", &self.entries.lock().unwrap().len())
+            .finish()
+    }
+}
+
+impl<T> ParseCache<T> {
+    /// Gets the parse result at `input`, only running the parser if there isn't a valid cached one
+    /// - Failed parses are not cached
+    pub fn parse<'a, P>(&self, input: PosWithInfo<'a>, parser: P) -> AstParseResult<'a, Arc<T>>
+    where
+        P: FnOnce(PosWithInfo<'a>) -> AstParseResult<'a, T>,
+    {
+        let state = &input.extra.0.state;
+
+        let entry = self.entries.lock().unwrap().get(&input.index).cloned();
+        if let Some(entry) = entry {
+            if state.lookups_valid(&entry.lookups, input) {
+                let (index, line, column) = entry.end;
+                let rest = PosWithInfo {
+                    line,
+                    column,
+                    index,
+                    input: &input.input[index - input.index..],
+                    extra: input.extra,
+                };
+                return Ok((rest, Arc::clone(&entry.value)));
+            }
+        }
+
+        let (result, lookups) = state.record_lookups(|| parser(input));
+        let (rest, value) = result?;
+
+        let value = Arc::new(value);
+        let entry = Entry {
+            value: Arc::clone(&value),
+            lookups,
+            end: (rest.index, rest.line, rest.column),
+        };
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(input.index, Arc::new(entry));
+
+        Ok((rest, value))
+    }
+}
+
+#[derive(Debug, Default)]
+/// Parse caches for code that is either a block of statements or an expression
+pub struct BodyCache {
+    pub statements: ParseCache<Statement>,
+    pub expression: ParseCache<Expression>,
+}
+
+impl BodyCache {
+    pub fn statement<'a>(&self, input: PosWithInfo<'a>) -> AstParseResult<'a, Arc<Statement>> {
+        self.statements.parse(input, Statement::parse)
+    }
+
+    pub fn expression<'a>(&self, input: PosWithInfo<'a>) -> AstParseResult<'a, Arc<Expression>> {
+        self.expression.parse(input, Expression::parse)
+    }
+}
@@ -1,32 +1,201 @@
+//! Contains class related structures
+
+use std::{collections::HashMap, sync::Arc};
+
 use nom::{
-    branch::alt, bytes::complete::tag, character::complete::char, combinator::fail, sequence::tuple,
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::char,
+    combinator::{fail, opt},
+    sequence::tuple,
+    Parser,
 };
 
 use crate::{
-    parsers::{identifier, PosWithInfo},
+    interpreter::runtime::{
+        error::{Location, StackFrame},
+        state::ClassState,
+        value::{Object, Value, PROTO_PROP},
+    },
+    parsers::{end_of_statement, identifier, types::Position, ws, ws1, PosWithInfo},
     runtime,
 };
 
-use super::parsers::AstParseResult;
+use super::{
+    cache::BodyCache,
+    function::FunctionDef,
+    parsers::AstParseResult,
+    statement::Statement,
+    variable::{VarType, VariableDecl},
+};
 
-#[derive(Debug, Default)]
-struct Class {
+#[derive(Debug, Clone)]
+/// A class declaration
+/// - Fields are declared like variables, and are evaluated for the instance
+/// - Methods are declared like functions, and are shared on the prototype
+pub struct Class {
     name: String,
+    line: usize,
+    body: Arc<ClassBody>,
+    methods: Vec<FunctionDef>,
+}
+
+#[derive(Debug)]
+/// Code of the class members
+/// - Fields are parsed when instantiating since they can depend on `this`
+pub struct ClassBody {
+    code: String,
+    location: Location,
+    cache: BodyCache,
+}
+
+enum Member {
+    Field,
+    Method(FunctionDef),
 }
 
 impl Class {
     pub fn parse(input: PosWithInfo) -> AstParseResult<Self> {
         let class = alt((tag("className"), tag("class")));
-        let name = identifier(fail::<_, (), _>);
+        let name = identifier(char('{'));
         let open = char('{');
-        let close = char('}');
 
-        let (input, (_, name, _, _)) = tuple((class, name, open, close))(input)?;
+        let line = input.line;
+        let (body_start, (_, _, name, _, _)) = tuple((class, ws1, name, ws, open))(input)?;
+
+        let member = alt((
+            VariableDecl::parse.map(|_| Member::Field),
+            FunctionDef::parse.map(Member::Method),
+        ));
+        // function expressions don't include the `!`
+        let mut member = tuple((ws, member, opt(end_of_statement))).map(|(_, member, _)| member);
+        let mut close = tuple((ws::<_, ()>, char('}')));
+
+        let mut input = body_start;
+        let mut methods = Vec::new();
+        loop {
+            if let Ok((input_after, _)) = close.parse(input) {
+                let body = ClassBody {
+                    code: body_start.input[..input.index - body_start.index].to_string(),
+                    location: body_start.into(),
+                    cache: BodyCache::default(),
+                };
+                return Ok((
+                    input_after,
+                    Self {
+                        name: name.to_string(),
+                        line,
+                        body: Arc::new(body),
+                        methods,
+                    },
+                ));
+            }
+
+            let (input_new, member) = member.parse(input)?;
+            input = input_new;
+            if let Member::Method(method) = member {
+                methods.push(method);
+            }
+        }
+    }
+
+    pub fn eval(&self, args: PosWithInfo) -> Result<(), runtime::Error> {
+        let interpreter = args.extra.0;
+        let state = &interpreter.state;
+
+        let mut prototype = Object::new(&state.realm, HashMap::new());
+        for method in &self.methods {
+            let func = method.func.eval(interpreter);
+            prototype.set_property(&method.name, func.into());
+        }
+
+        let mut properties = HashMap::new();
+        properties.insert("prototype".to_string(), state.heap.alloc(prototype).into());
+        let class = state.heap.alloc(Object::new(&state.realm, properties));
+
+        state.add_class(ClassState::new(&class, Arc::clone(&self.body)));
+        state.add_var(&self.name, class.into(), self.line, VarType::VarVar, None);
 
-        todo!()
+        Ok(())
     }
+}
+
+#[derive(Debug, Clone)]
+/// Instantiation of a class with `new`
+pub struct New {
+    name: String,
+    location: Location,
+}
+
+impl New {
+    pub fn parse(input: PosWithInfo) -> AstParseResult<Self> {
+        let location = input.into();
+        let name = identifier(fail::<_, (), _>);
+
+        let (input, (_, _, name)) = tuple((tag("new"), ws1, name))(input)?;
+
+        Ok((
+            input,
+            Self {
+                name: name.to_string(),
+                location,
+            },
+        ))
+    }
+
+    pub fn eval(&self, args: PosWithInfo) -> Result<Value, runtime::Error> {
+        let state = &args.extra.0.state;
+
+        let class = state
+            .get_var(&self.name)
+            .and_then(|var| match var.get_value() {
+                Value::Object(Some(obj)) => state.get_class(obj),
+                _ => None,
+            })
+            .ok_or_else(|| runtime::Error::Type(format!("{} is not a class", self.name)))?;
+
+        // there can only be one
+        if !class.take_instance() {
+            return Err(runtime::Error::MultipleInstances(self.name.to_string()));
+        }
+
+        let prototype = class
+            .obj()
+            .and_then(|obj| obj.lock().unwrap().get_property("prototype"))
+            .unwrap_or(Value::Object(None));
+        let mut properties = HashMap::new();
+        properties.insert(PROTO_PROP.to_string(), prototype);
+        let instance = state.heap.alloc(Object::new(&state.realm, properties));
+
+        // fields are evaluated like a function call, so they can use `this` and each other
+        let frame = StackFrame {
+            name: self.name.to_string(),
+            call_site: self.location,
+        };
+        let body = class.body();
+        state.call_frame(frame, || {
+            state.add_var("this", instance.clone().into(), 0, VarType::VarVar, None);
+
+            let mut code_with_pos = Position {
+                line: body.location.line,
+                column: body.location.column,
+                index: 0,
+                input: body.code.as_str(),
+                extra: args.extra,
+            };
+            while let Ok((code_after, statement)) = body.cache.statement(code_with_pos) {
+                if let Statement::VariableDecl(field) = &*statement {
+                    statement.eval_traced(code_with_pos, code_after)?;
+                    for name in field.name.names() {
+                        let value = state.get_var_value(name, 0).unwrap_or(Value::Undefined);
+                        instance.lock().unwrap().set_property(name, value);
+                    }
+                }
+                code_with_pos = code_after;
+            }
+            Ok(())
+        })?;
 
-    pub fn eval(args: PosWithInfo) -> Result<(), runtime::Error> {
-        todo!()
+        Ok(instance.into())
     }
 }
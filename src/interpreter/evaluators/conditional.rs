@@ -12,27 +12,35 @@ use nom::{
 use crate::{
     interpreter::evaluators::scope::scope,
     parsers::{identifier, types::Position, ws, ws1, PosWithInfo},
-    runtime::{self, value::Value},
+    runtime::{self, error::Location, state::InterpreterState, value::Value},
 };
 
-use super::{expression::Expression, parsers::AstParseResult, statement::Statement};
+use super::{
+    cache::BodyCache, expression::Expression, parsers::AstParseResult, statement::Statement,
+};
 
 #[derive(Debug, Clone)]
 pub struct When {
     expression: String,
     body: String,
-    body_line: usize,
+    body_location: Location,
     // TODO: store the previous value in the state, since prev keyword is a thing
+    /// Values of `identifiers` when the `when` is evaluated, and after each update
     prev_identifier_values: Arc<Mutex<Vec<Option<Value>>>>,
     identifiers: Vec<String>,
     // TODO: to prevent recursive invokes, make this vec later on
     else_when: Option<ElseWhen>,
+    cache: Arc<BodyCache>,
 }
 
 #[derive(Debug, Clone)]
 pub enum ElseWhen {
     When(Box<When>),
-    Else { body: String, body_line: usize },
+    Else {
+        body: String,
+        body_location: Location,
+        cache: Arc<BodyCache>,
+    },
 }
 
 impl When {
@@ -48,16 +56,18 @@ impl When {
         // TODO: probably don't make it recursive in call later
         let else_when =
             tuple((else_(), ws1, When::parse)).map(|(_, _, when)| ElseWhen::When(Box::new(when)));
-        let else_ =
-            tuple((else_(), consumed(ws1), body())).map(|(_, (ws_pos, _), body)| ElseWhen::Else {
+        let else_ = tuple((else_(), ws1, consumed(body()))).map(|(_, _, (body_consumed, body))| {
+            ElseWhen::Else {
                 body: body.to_string(),
-                body_line: ws_pos.line,
-            });
+                body_location: body_consumed.into(),
+                cache: Arc::default(),
+            }
+        });
 
         let (input, (_, _, expression, _, (body_consumed, body), _)) =
             tuple((when, ws1, expression, ws, consumed(body()), ws))(input)?;
 
-        let body_line = body_consumed.line;
+        let body_location = body_consumed.into();
 
         let (input, else_when) = opt(alt((else_when, else_)))(input)?;
 
@@ -70,29 +80,52 @@ impl When {
         )(expression)
         .unwrap();
 
-        let interpreter = input.extra.0;
-        let values = identifiers.iter().map(|i| {
-            interpreter
-                .state
-                .get_var(i)
-                .map(|var| var.get_value().to_owned())
-        });
-
         Ok((
             input,
             Self {
                 expression: expression.to_string(),
                 body: body.to_string(),
-                body_line,
-                prev_identifier_values: Arc::new(Mutex::new(values.collect())),
+                body_location,
+                prev_identifier_values: Arc::default(),
                 identifiers,
                 else_when,
+                cache: Arc::default(),
             },
         ))
     }
 
     pub fn eval(&self, args: PosWithInfo) {
-        args.extra.0.state.push_when(self);
+        let state = &args.extra.0.state;
+        state.push_when(self.with_current_values(state));
+    }
+
+    /// Copies the `when` with the current values of the identifiers it watches
+    /// - The parsed `when` can be evaluated many times, so each evaluation gets its own values
+    fn with_current_values(&self, state: &InterpreterState) -> Self {
+        let values = self
+            .identifiers
+            .iter()
+            .map(|i| state.get_var(i).map(|var| var.get_value().to_owned()))
+            .collect();
+        let else_when = self.else_when.as_ref().map(|else_when| match else_when {
+            ElseWhen::When(when) => ElseWhen::When(Box::new(when.with_current_values(state))),
+            else_ => else_.clone(),
+        });
+
+        Self {
+            prev_identifier_values: Arc::new(Mutex::new(values)),
+            else_when,
+            ..self.clone()
+        }
+    }
+
+    /// Names used by the condition, including the conditions of `else when`
+    pub fn identifiers(&self) -> Vec<&String> {
+        let mut identifiers = self.identifiers.iter().collect::<Vec<_>>();
+        if let Some(ElseWhen::When(when)) = &self.else_when {
+            identifiers.extend(when.identifiers());
+        }
+        identifiers
     }
 
     pub fn eval_body(
@@ -101,7 +134,9 @@ impl When {
         var_name: &str,
         new_value: Value,
     ) -> Result<(), runtime::Error> {
-        let Some(identifier_index) = self.identifiers.iter().position(|i| i == var_name) else {
+        // a property such as `obj.prop` is watched with the variable holding it
+        let watches = |i: &String| i.split(['.', '[']).next() == Some(var_name);
+        let Some(identifier_index) = self.identifiers.iter().position(watches) else {
             // not found
             return self.else_when_exec(args, false, var_name, new_value);
         };
@@ -110,6 +145,8 @@ impl When {
         let prev_value = &prev_values[identifier_index];
 
         let changed = match prev_value {
+            // objects can change in place, so assigning the same object is a change too
+            Some(_) if matches!(new_value, Value::Object(Some(_))) => true,
             Some(prev_value) => !prev_value.strict_eq(&new_value), // has it changed?
             None => true,                                          // declaration of new value
         };
@@ -125,14 +162,14 @@ impl When {
 
         // TODO: does position matter
         let code_with_pos = Position::new_with_extra(self.expression.as_str(), args.extra);
-        let (_, expr) = Expression::parse(code_with_pos).unwrap();
+        let (_, expr) = self.cache.expression(code_with_pos).unwrap();
         let value = expr.eval(args)?;
 
         if !bool::from(value.0.as_ref()) {
             return self.else_when_exec(args, true, var_name, new_value);
         }
 
-        Self::body_exec(args, &self.body, self.body_line)
+        Self::body_exec(args, &self.body, self.body_location, &self.cache)
     }
 
     fn else_when_exec(
@@ -148,25 +185,54 @@ impl When {
 
         match else_when {
             ElseWhen::When(when) => when.eval_body(args, var_name, new_value),
-            ElseWhen::Else { body, body_line } => {
+            ElseWhen::Else {
+                body,
+                body_location,
+                cache,
+            } => {
                 if !found {
                     return Ok(());
                 }
-                Self::body_exec(args, body, *body_line)
+                Self::body_exec(args, body, *body_location, cache)
             }
         }
     }
 
-    fn body_exec(args: PosWithInfo, body: &str, body_line: usize) -> Result<(), runtime::Error> {
+    fn body_exec(
+        args: PosWithInfo,
+        body: &str,
+        body_location: Location,
+        cache: &BodyCache,
+    ) -> Result<(), runtime::Error> {
+        let state = &args.extra.0.state;
+        let _depth = state.budget.when()?;
+
         // parse and execute the body
-        args.extra.0.state.push_scope(body_line);
+        let scope_depth = state.scope_depth();
+        state.push_scope(body_location.line);
+
+        let code_with_pos = Position {
+            line: body_location.line,
+            column: body_location.column,
+            index: 0,
+            input: body,
+            extra: args.extra,
+        };
+        let res = Self::body_statements(code_with_pos, cache);
 
-        let mut code_with_pos = Position::new_with_extra(body, args.extra);
+        // the body is left at its closing brace or a `return`, so its scopes are popped here
+        state.pop_scopes_to(scope_depth, body_location.line);
+        res
+    }
 
+    fn body_statements(
+        mut code_with_pos: PosWithInfo,
+        cache: &BodyCache,
+    ) -> Result<(), runtime::Error> {
         let mut scope_count = 0usize;
 
-        while let Ok((code_after, statement)) = Statement::parse(code_with_pos) {
-            match statement {
+        while let Ok((code_after, statement)) = cache.statement(code_with_pos) {
+            match *statement {
                 Statement::ScopeStart(_) => {
                     scope_count = scope_count.checked_add(1).expect("scope count overflow")
                 }
@@ -179,20 +245,14 @@ impl When {
                 _ => (),
             }
 
+            let ret = statement.eval_traced(code_with_pos, code_after)?;
             code_with_pos = code_after;
 
-            let ret = statement.eval(code_with_pos)?;
             if ret.return_value.is_some() {
-                args.extra.0.state.pop_scope(code_with_pos.line);
                 return Ok(());
             }
         }
 
-        let (_, statements) = many0(Statement::parse)(code_with_pos).unwrap();
-        for statement in statements {
-            statement.eval(args)?;
-        }
-
         Ok(())
     }
 }
@@ -200,9 +260,9 @@ impl When {
 // a vec of statements instead of a string like a function
 // this is because if statement shouldn't be able to introduce side effects like
 // defining a variable named `{` or `if` and such
-type Body = Vec<Statement>;
+type Body = Vec<(Location, Statement)>;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct If {
     expression: Expression,
     body: Body,
@@ -210,7 +270,7 @@ pub struct If {
     else_: Option<Body>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct ElseIf {
     expression: Expression,
     body: Body,
@@ -227,12 +287,14 @@ impl If {
             let mut bracket_start =
                 verify(Statement::parse, |s| matches!(s, Statement::ScopeStart(_)));
 
+            let start: Location = ws::<PosWithInfo, ()>(input).unwrap().0.into();
             let (mut input, bracket_start) = bracket_start(input)?;
 
             let mut depth = 0usize;
-            let mut statements = vec![bracket_start];
+            let mut statements = vec![(start, bracket_start)];
 
             loop {
+                let location = ws::<_, ()>(input).unwrap().0.into();
                 let (input_, statement) = Statement::parse(input)?;
                 input = input_;
                 match statement {
@@ -245,7 +307,7 @@ impl If {
                     }
                     _ => {}
                 }
-                statements.push(statement);
+                statements.push((location, statement));
             }
 
             Ok((input, statements))
@@ -272,13 +334,18 @@ impl If {
         ))
     }
 
-    pub fn eval(&self, args: PosWithInfo) -> Result<(), runtime::Error> {
+    /// # Returns
+    /// - The value of a `return` statement in the body, if any
+    pub fn eval(&self, args: PosWithInfo) -> Result<Option<Value>, runtime::Error> {
         let if_expr = |expr: &Expression| Ok(expr.eval(args)?.0.as_ref().into());
         let exec_body = |body: &Body| {
-            for statement in body {
-                statement.eval(args)?;
+            for (location, statement) in body {
+                let ret = statement.eval_at(*location, args)?;
+                if ret.return_value.is_some() {
+                    return Ok(ret.return_value);
+                }
             }
-            Ok(())
+            Ok(None)
         };
 
         if if_expr(&self.expression)? {
@@ -295,6 +362,6 @@ impl If {
             return exec_body(else_);
         }
 
-        Ok(())
+        Ok(None)
     }
 }
@@ -4,7 +4,7 @@ use crate::parsers::{end_of_statement, ws, PosWithInfo};
 
 use super::parsers::AstParseResult;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Reverse;
 
 impl Reverse {
@@ -0,0 +1,138 @@
+//! Contains the `delete` statement
+
+use std::borrow::Cow;
+
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_while1},
+    combinator::{map_opt, verify},
+    sequence::tuple,
+    Parser,
+};
+
+use crate::{
+    interpreter::runtime::error::Error,
+    parsers::{end_of_statement, ws, ws1, PosWithInfo},
+    runtime::value::Value,
+};
+
+use super::{
+    expression::{Atom, AtomValue, Expression, Time},
+    parsers::AstParseResult,
+};
+
+/// Keywords that can be deleted, after which statements using them fail
+pub const KEYWORDS: &[&str] = &[
+    "class",
+    "className",
+    "const",
+    "delete",
+    "else",
+    "export",
+    "function",
+    "if",
+    "import",
+    "return",
+    "reverse",
+    "var",
+    "when",
+];
+
+#[derive(Debug, Clone)]
+/// Deletes a variable, a property, a primitive or a keyword
+/// - Deleted primitives and keywords can't be used anymore by the interpreter
+pub enum Delete {
+    Keyword(String),
+    Atom(Atom),
+}
+
+impl Delete {
+    pub fn parse(input: PosWithInfo) -> AstParseResult<Self> {
+        let keyword = verify(take_while1(char::is_alphanumeric), |word: &PosWithInfo| {
+            KEYWORDS.contains(&word.input)
+        });
+        let keyword = tuple((keyword, ws, end_of_statement))
+            .map(|(keyword, _, _): (PosWithInfo, _, _)| Delete::Keyword(keyword.to_string()));
+        let atom = map_opt(
+            tuple((Expression::parse, end_of_statement)),
+            |(expr, _)| match expr {
+                Expression::Atom(atom) => Some(Delete::Atom(atom)),
+                _ => None,
+            },
+        );
+
+        let (input, (_, _, delete)) = tuple((tag("delete"), ws1, alt((keyword, atom))))(input)?;
+
+        Ok((input, delete))
+    }
+
+    pub fn eval(&self, args: PosWithInfo) -> Result<(), Error> {
+        let state = &args.extra.0.state;
+
+        let atom = match self {
+            Delete::Keyword(keyword) => {
+                state.delete_keyword(keyword);
+                return Ok(());
+            }
+            Delete::Atom(atom) => atom,
+        };
+
+        match (&atom.value, atom.postfix.split_last()) {
+            (
+                AtomValue::Variable {
+                    name,
+                    time: Time::Previous(0),
+                },
+                None,
+            ) => {
+                if state.check_write(name, false)? {
+                    state.delete_var(name);
+                }
+            }
+            (
+                AtomValue::Variable {
+                    name,
+                    time: Time::Previous(0),
+                },
+                Some((property, path)),
+            ) => {
+                if !state.check_write(name, true)? {
+                    return Ok(());
+                }
+                let mut value =
+                    Cow::Owned(state.get_var_value(name, 0).unwrap_or(Value::Undefined));
+                for postfix in path {
+                    value = postfix.eval(value, args)?.0;
+                }
+                property.delete(value, args)?;
+            }
+            (AtomValue::Value(value), None) => state.delete_value(value.to_owned()),
+            (AtomValue::Deleted(name), _) => return Err(Error::Deleted(name.to_string())),
+            _ => {
+                return Err(Error::Type(
+                    "Only variables, properties, primitives and keywords can be deleted"
+                        .to_string(),
+                ))
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Finds a deleted keyword at the start of a statement, such as `var` in `const var a = 1!`
+pub fn deleted_keyword(input: PosWithInfo) -> Option<String> {
+    let state = &input.extra.0.state;
+
+    let mut input = input;
+    loop {
+        let (rest, word) = take_while1::<_, _, ()>(char::is_alphanumeric)(input).ok()?;
+        if !KEYWORDS.contains(&word.input) {
+            return None;
+        }
+        if state.is_keyword_deleted(word.input) {
+            return Some(word.to_string());
+        }
+        (input, _) = ws1(rest).ok()?;
+    }
+}
@@ -18,13 +18,17 @@ use crate::{
     interpreter::runtime::{error::Error, state::DefineType, value::Value},
     parsers::{types::Position, *},
     prelude::Wrapper,
-    runtime::{state::FunctionVariant, value::ObjectRef},
+    runtime::{
+        state::FunctionVariant,
+        stdlib::array,
+        value::{string, Hint, ObjectRef, PropertyKey},
+    },
     Interpreter,
 };
 
 use super::{
-    array::ArrayInitialiser, function::FunctionCall, object::ObjectInitialiser,
-    parsers::AstParseResult, scope::scope,
+    array::ArrayInitialiser, cache::BodyCache, class::New, function::FunctionCall,
+    object::ObjectInitialiser, parsers::AstParseResult, scope::scope, template::Template,
 };
 
 #[derive(Debug, Clone)]
@@ -303,7 +307,7 @@ impl From<Atom> for Expression {
 }
 
 impl Expression {
-    pub fn eval(&self, args: PosWithInfo) -> Result<Wrapper<Cow<Value>>, Error> {
+    pub fn eval(&self, args: PosWithInfo) -> Result<Wrapper<Cow<'_, Value>>, Error> {
         match self {
             Expression::Atom(atom) => atom.eval(args),
             Expression::UnaryOperation { operator, right } => operator.eval(right, args),
@@ -314,6 +318,7 @@ impl Expression {
             } => {
                 let left = left.eval(args)?;
                 let right = right.eval(args)?;
+                let (left, right) = operator.to_primitives(left, right, args)?;
 
                 let value = match operator {
                     Operator::Equal => Value::Boolean(left.loose_eq(&right)?),
@@ -359,31 +364,57 @@ impl Expression {
 // everything in here isn't evaluated until `eval`
 pub struct Atom {
     pub value: AtomValue,
-    postfix: Vec<AtomPostfix>,
+    pub postfix: Vec<AtomPostfix>,
 }
 
 #[derive(Debug, Clone)]
 pub enum AtomValue {
     Value(Value),
+    /// Variable that is looked up when evaluated
+    Variable {
+        name: String,
+        time: Time,
+    },
     FunctionCall(FunctionCall),
+    New(New),
     ObjectInitialiser(ObjectInitialiser),
     ArrayInitialiser(ArrayInitialiser),
     FunctionDef(FunctionExpr),
+    Template(Template),
+    /// A primitive that was deleted, which fails when evaluated
+    Deleted(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Which value of a variable is read, from `previous`, `current` and `next`
+/// - With properties, this is the value of the last property rather than the variable
+pub enum Time {
+    /// Value from this many assignments ago, where 0 is the current value
+    Previous(usize),
+    /// Value of the next assignment, which suspends the statement until then
+    Next,
 }
 
 #[derive(Debug, Clone)]
 pub struct FunctionExpr {
     arg_names: Vec<String>,
-    body: String,
+    body: Arc<String>,
     pub body_line: usize,
+    body_column: usize,
+    cache: Arc<BodyCache>,
+    /// Calls take turns with the main program instead of running to completion
+    pub is_async: bool,
 }
 
 impl From<&FunctionExpr> for FunctionVariant {
     fn from(func: &FunctionExpr) -> Self {
         Self::FunctionDefined {
             body_line: func.body_line,
-            body: Arc::new(func.body.clone()),
+            body_column: func.body_column,
+            body: Arc::clone(&func.body),
             arg_names: Arc::new(func.arg_names.clone()),
+            cache: Arc::clone(&func.cache),
+            is_async: func.is_async,
         }
     }
 }
@@ -415,6 +446,7 @@ impl FunctionExpr {
         let (body, (args, _)) = tuple((alt((arrow().map(|_| Vec::new()), args)), ws))(input)?;
 
         let body_line = body.line;
+        let body_column = body.column;
 
         let (input, body) = alt((recognize(scope), recognize(Expression::parse)))(body)?;
 
@@ -422,8 +454,11 @@ impl FunctionExpr {
             input,
             FunctionExpr {
                 arg_names: args,
-                body: body.to_string(),
+                body: Arc::new(body.to_string()),
                 body_line,
+                body_column,
+                cache: Arc::default(),
+                is_async: false,
             },
         ))
     }
@@ -482,57 +517,102 @@ impl AtomPostfix {
         }
     }
 
-    pub fn eval(&self, value: Cow<Value>, args: PosWithInfo) -> Result<Wrapper<Cow<Value>>, Error> {
-        let Value::Object(obj) = value.into_owned() else {
-            return Err(Error::Type("Cannot read properties".to_string()));
-        };
+    pub fn eval(
+        &self,
+        value: Cow<Value>,
+        args: PosWithInfo,
+    ) -> Result<Wrapper<Cow<'_, Value>>, Error> {
+        let key = self.key(args)?;
+        if let Value::String(value) = value.as_ref() {
+            let value = string::property(value, &key).unwrap_or(Value::Undefined);
+            return Ok(Wrapper(Cow::Owned(value)));
+        }
 
-        let Some(obj) = obj else {
-            return Err(Error::Type("Cannot read properties of null".to_string()));
-        };
+        let obj = Self::object(value)?;
+        let obj = obj.lock().unwrap();
+
+        let realm = &args.extra.0.state.realm;
+        let value = obj
+            .get(&key)
+            .or_else(|| array::property(realm, &obj, &key))
+            .unwrap_or(Value::Undefined);
+        Ok(Wrapper(Cow::Owned(value)))
+    }
 
+    /// Gets the value the property had `steps` assignments ago
+    pub fn eval_previous(
+        &self,
+        value: Cow<Value>,
+        args: PosWithInfo,
+        steps: usize,
+    ) -> Result<Value, Error> {
+        let key = self.key(args)?;
+        let obj = Self::object(value)?;
         let obj = obj.lock().unwrap();
 
+        Ok(obj.get_previous_property(&key, steps))
+    }
+
+    /// Removes the property from the object
+    pub fn delete(&self, value: Cow<Value>, args: PosWithInfo) -> Result<(), Error> {
+        let key = self.key(args)?;
+        let obj = Self::object(value)?;
+
+        obj.lock().unwrap().remove_property(&key);
+        Ok(())
+    }
+
+    /// Gets the property key, which converts the value in brackets like JS does
+    pub fn key(&self, args: PosWithInfo) -> Result<PropertyKey, Error> {
         match self {
-            AtomPostfix::DotNotation(property) => {
-                if let Some(value) = obj.get_property(property) {
-                    return Ok(Wrapper(Cow::Owned(value.clone())));
-                }
-            }
+            AtomPostfix::DotNotation(property) => Ok(property.as_str().into()),
             AtomPostfix::BracketNotation(expr) => {
-                let value = expr.eval(args)?;
-                let value = value.0.as_ref();
-
-                match value {
-                    Value::String(str) => {
-                        if let Some(value) = obj.get_property(str) {
-                            return Ok(Wrapper(Cow::Owned(value.clone())));
-                        }
-                    }
-                    Value::Number(num) => {
-                        // TODO: eventually handle floats, for now convert to int
-                        let num = *num as i64;
-                        if let Some(value) = obj.get_property(&num.to_string()) {
-                            return Ok(Wrapper(Cow::Owned(value.clone())));
-                        }
-                    }
-                    _ => (),
-                }
+                PropertyKey::from_value(expr.eval(args)?.0.as_ref(), args)
             }
         }
+    }
+
+    fn object(value: Cow<Value>) -> Result<ObjectRef, Error> {
+        let Value::Object(obj) = value.into_owned() else {
+            return Err(Error::Type("Cannot read properties".to_string()));
+        };
 
-        Ok(Wrapper(Cow::Owned(Value::Undefined)))
+        obj.ok_or_else(|| Error::Type("Cannot read properties of null".to_string()))
     }
 }
 
 impl Atom {
-    pub fn eval(&self, args: PosWithInfo) -> Result<Wrapper<Cow<Value>>, Error> {
+    pub fn eval(&self, args: PosWithInfo) -> Result<Wrapper<Cow<'_, Value>>, Error> {
         let mut value = match &self.value {
             AtomValue::Value(value) => Cow::Borrowed(value),
+            AtomValue::Variable { name, time } => {
+                let state = &args.extra.0.state;
+                match (time, self.postfix.split_last()) {
+                    // history of the property rather than the variable holding the object
+                    (Time::Previous(steps @ 1..), Some((property, path))) => {
+                        let mut value =
+                            Cow::Owned(state.get_var_value(name, 0).unwrap_or(Value::Undefined));
+                        for postfix in path {
+                            value = postfix.eval(value, args)?.0;
+                        }
+                        let value = property.eval_previous(value, args, *steps)?;
+                        return Ok(Wrapper(Cow::Owned(value)));
+                    }
+                    (Time::Previous(steps), _) => Cow::Owned(
+                        state
+                            .get_var_value(name, *steps)
+                            .unwrap_or(Value::Undefined),
+                    ),
+                    (Time::Next, _) => Cow::Owned(state.get_next_var_value(name)?),
+                }
+            }
             AtomValue::FunctionCall(expr) => Cow::Owned(expr.eval(args)?),
+            AtomValue::New(expr) => Cow::Owned(expr.eval(args)?),
             AtomValue::ObjectInitialiser(expr) => Cow::Owned(expr.eval(args)?),
             AtomValue::ArrayInitialiser(expr) => Cow::Owned(expr.eval(args)?),
             AtomValue::FunctionDef(expr) => Cow::Owned(expr.eval(args.extra.0).into()),
+            AtomValue::Template(expr) => Cow::Owned(expr.eval(args)?),
+            AtomValue::Deleted(name) => return Err(Error::Deleted(name.to_string())),
         };
 
         for postfix in &self.postfix {
@@ -567,6 +647,15 @@ impl Atom {
                 return Ok((input, Atom { value, postfix }));
             }
 
+            // the identifier could end where the implicit string would, like `k` in `obj[k]`
+            if let Some(mut term) = implicit_string_term {
+                let term = move |input| term.parse(input).map(|(input, _)| (input, ()));
+                if let Ok((input, value)) = AtomValue::parse(input, Some(term)) {
+                    let (input, postfix) = many0(AtomPostfix::parse)(input)?;
+                    return Ok((input, Atom { value, postfix }));
+                }
+            }
+
             // last resort, will return implicit string if all fails
             let (input, value) = AtomValue::parser_last_resort(implicit_string_term)(input);
 
@@ -592,44 +681,51 @@ impl AtomValue {
             return Ok((input, AtomValue::FunctionCall(value)));
         }
 
+        if let Ok((input, value)) = New::parse(input) {
+            return Ok((input, AtomValue::New(value)));
+        }
+
+        // functions followed by a postfix weren't called above, so they are used as a value
+        let has_postfix = postfix_separator.is_some();
         let variable_parse =
             |chunk: Position<_>| match input.extra.0.state.get_identifier(chunk.input, chunk) {
-                Some(defined) => {
-                    if let DefineType::Var(var) = defined {
-                        Some(var)
-                    } else {
-                        None
-                    }
-                }
-                None => None,
+                Some(DefineType::Var) => Some(chunk.input.to_string()),
+                Some(DefineType::Func(_)) if has_postfix => Some(chunk.input.to_string()),
+                _ => None,
             };
 
-        // TODO: add unit tests
-        let (input, has_previous) = opt(tuple((tag("previous"), ws1)))(input)?;
-        let has_previous = has_previous.is_some();
+        let variable = |input| {
+            let res = match postfix_separator.clone() {
+                Some(postfix_separator) => alt((
+                    map_opt(
+                        identifier(alt((char('!').map(|_| ()), postfix_separator))),
+                        variable_parse,
+                    ),
+                    map_opt(chunk, variable_parse),
+                ))(input),
+                None => alt((
+                    map_opt(terminated_chunk::<_, ()>, variable_parse),
+                    map_opt(chunk, variable_parse),
+                ))(input),
+            };
+            res.map_err(|_| nom::Err::Error(nom::error::Error::new(input, ErrorKind::Verify)))
+        };
+
+        // `previous previous x` goes back two assignments
+        let previous =
+            many1(tuple((tag("previous"), ws1))).map(|steps| Time::Previous(steps.len()));
+        let current = tuple((tag("current"), ws1)).map(|_| Time::Previous(0));
+        let next = tuple((tag("next"), ws1)).map(|_| Time::Next);
+        let time = opt(alt((previous, current, next)));
 
         // variable?
-        let variable_parse_result = match postfix_separator {
-            Some(postfix_separator) => alt((
-                map_opt(
-                    identifier(alt((char('!').map(|_| ()), postfix_separator))),
-                    variable_parse,
-                ),
-                map_opt(chunk, variable_parse),
-            ))(input),
-            None => alt((
-                map_opt(terminated_chunk::<_, ()>, variable_parse),
-                map_opt(chunk, variable_parse),
-            ))(input),
-        };
-        if let Ok((input, var)) = variable_parse_result {
+        // without the keyword too, since the variable itself could be called `next` and such
+        let variable_with_time =
+            tuple((time, variable)).map(|(time, name)| (time.unwrap_or(Time::Previous(0)), name));
+        let variable_without_time = variable.map(|name| (Time::Previous(0), name));
+        if let Ok((input, (time, name))) = alt((variable_with_time, variable_without_time))(input) {
             // TODO: function call
-            let value = if has_previous {
-                var.get_previous_value().clone()
-            } else {
-                var.get_value().clone()
-            };
-            return Ok((input, AtomValue::Value(value)));
+            return Ok((input, AtomValue::Variable { name, time }));
         }
 
         // func def
@@ -651,9 +747,20 @@ impl AtomValue {
         P: Parser<PosWithInfo<'a>, PosWithInfo<'a>, ()> + Copy,
     {
         move |input| {
+            let state = &input.extra.0.state;
+
+            // strings with interpolations, before other strings
+            if let Ok((rest, template)) = Template::parse(input) {
+                return (rest, AtomValue::Template(template));
+            }
+
             // actual value?
-            if let Ok((input, value)) = Value::parse(input) {
-                return (input, AtomValue::Value(value));
+            if let Ok((rest, value)) = Value::parse(input) {
+                if state.is_value_deleted(&value) {
+                    let literal = &input.input[..input.input.len() - rest.input.len()];
+                    return (rest, AtomValue::Deleted(literal.to_string()));
+                }
+                return (rest, AtomValue::Value(value));
             }
 
             // object initialiser
@@ -676,17 +783,36 @@ impl AtomValue {
                     rest,
                 ))(input)
                 .unwrap(),
-                None => alt((take_until::<_, _, ()>("!"), rest))(input).unwrap(),
+                None => take_until_parser::<_, (), _, _>(alt((
+                    implicit_string_op,
+                    value((), char('!')),
+                )))(input)
+                .unwrap(),
             };
 
-            (
-                input,
-                AtomValue::Value(Value::String(str.input.to_string())),
-            )
+            let value = Value::String(str.input.to_string());
+            if state.is_value_deleted(&value) {
+                return (input, AtomValue::Deleted(str.input.to_string()));
+            }
+            (input, AtomValue::Value(value))
         }
     }
 }
 
+/// Ends an implicit string at an operator followed by a value, like ` + 1` in `hello + 1`
+/// - The operator needs whitespace around it, so strings like `well-known` aren't split
+fn implicit_string_op(input: PosWithInfo) -> IResult<PosWithInfo, (), ()> {
+    let (right, _) = tuple((ws1, Operator::parse, ws1))(input).map_err(|_| nom::Err::Error(()))?;
+
+    let is_value = Value::parse(right).is_ok()
+        || AtomValue::parse::<fn(PosWithInfo) -> _>(right, None).is_ok();
+    if !is_value {
+        return Err(nom::Err::Error(()));
+    }
+
+    Ok((input, ()))
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum UnaryOperator {
     Not,
@@ -698,10 +824,10 @@ impl UnaryOperator {
         &'a self,
         right: &'a Expression,
         args: PosWithInfo,
-    ) -> Result<Wrapper<Cow<Value>>, Error> {
+    ) -> Result<Wrapper<Cow<'a, Value>>, Error> {
         let value = match self {
             UnaryOperator::Not => !right.eval(args)?,
-            UnaryOperator::Minus => (-right.eval(args)?)?,
+            UnaryOperator::Minus => (-to_primitive(right.eval(args)?, args, Hint::Number)?)?,
         };
 
         Ok(value)
@@ -761,6 +887,52 @@ impl Operator {
     }
 }
 
+impl Operator {
+    /// Converts objects the operator is used on to primitives, which can call methods of the objects
+    /// - `==` only converts an object that is compared to a primitive, objects are otherwise compared by reference
+    fn to_primitives<'a>(
+        self,
+        left: Operand<'a>,
+        right: Operand<'a>,
+        args: PosWithInfo,
+    ) -> Result<(Operand<'a>, Operand<'a>), Error> {
+        let hint = match self {
+            Operator::StrictEqual | Operator::StrictNotEqual | Operator::And | Operator::Or => {
+                return Ok((left, right))
+            }
+            Operator::Equal | Operator::NotEqual => {
+                let nullish_or_object =
+                    |value: &Value| matches!(value, Value::Object(_) | Value::Undefined);
+                if nullish_or_object(&left) == nullish_or_object(&right) {
+                    return Ok((left, right));
+                }
+                Hint::Default
+            }
+            Operator::Add => Hint::Default,
+            _ => Hint::Number,
+        };
+
+        Ok((
+            to_primitive(left, args, hint)?,
+            to_primitive(right, args, hint)?,
+        ))
+    }
+}
+
+type Operand<'a> = Wrapper<Cow<'a, Value>>;
+
+/// Converts the value to a primitive if it's an object
+fn to_primitive<'a>(
+    value: Operand<'a>,
+    args: PosWithInfo,
+    hint: Hint,
+) -> Result<Operand<'a>, Error> {
+    match value.as_ref() {
+        Value::Object(Some(_)) => Ok(Wrapper(Cow::Owned(value.to_primitive(args, hint)?))),
+        _ => Ok(value),
+    }
+}
+
 impl From<Operator> for usize {
     fn from(value: Operator) -> Self {
         match value {
@@ -0,0 +1,108 @@
+//! Contains `export` and `import` statements
+
+use nom::{bytes::complete::tag, combinator::fail, sequence::tuple};
+
+use crate::{
+    interpreter::runtime::error::Error,
+    parsers::{end_of_statement, identifier, ws, ws1, PosWithInfo},
+    runtime::value::Value,
+};
+
+use super::{parsers::AstParseResult, variable::VarType};
+
+#[derive(Debug, Clone)]
+/// Exports a variable to another file
+pub struct Export {
+    name: String,
+    file: String,
+}
+
+impl Export {
+    pub fn parse(input: PosWithInfo) -> AstParseResult<Self> {
+        let name = identifier(fail::<_, (), _>);
+
+        // export ws+ identifier ws+ "to" ws+ string ws* "!"
+        let (input_after, (_, _, name, _, _, _, file, _, _)) = tuple((
+            tag("export"),
+            ws1,
+            name,
+            ws1,
+            tag("to"),
+            ws1,
+            Value::parse,
+            ws,
+            end_of_statement,
+        ))(input)?;
+
+        let Value::String(file) = file else {
+            return Err(nom::Err::Error(nom::error::Error::new(
+                input,
+                nom::error::ErrorKind::Verify,
+            )));
+        };
+
+        Ok((
+            input_after,
+            Self {
+                name: name.to_string(),
+                file,
+            },
+        ))
+    }
+
+    pub fn eval(&self, args: PosWithInfo) -> Result<(), Error> {
+        let state = &args.extra.0.state;
+        let value = state.get_var_value(&self.name, 0).ok_or_else(|| {
+            Error::RuntimeException(format!("Can't export {}, it's not declared", self.name))
+        })?;
+        state
+            .files
+            .lock()
+            .unwrap()
+            .export(&self.file, &self.name, value);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Imports a variable that was exported to this file
+pub struct Import {
+    name: String,
+    line: usize,
+}
+
+impl Import {
+    pub fn parse(input: PosWithInfo) -> AstParseResult<Self> {
+        let line = input.line;
+        let name = identifier(end_of_statement);
+
+        let (input, (_, _, name, _, _)) =
+            tuple((tag("import"), ws1, name, ws, end_of_statement))(input)?;
+
+        Ok((
+            input,
+            Self {
+                name: name.to_string(),
+                line,
+            },
+        ))
+    }
+
+    pub fn eval(&self, args: PosWithInfo) -> Result<(), Error> {
+        let state = &args.extra.0.state;
+        let value = state
+            .files
+            .lock()
+            .unwrap()
+            .import(&self.name)
+            .ok_or_else(|| Error::NotExported(self.name.to_string()))?;
+        state.add_var_runtime(
+            &self.name,
+            value,
+            self.line,
+            VarType::ConstConst,
+            None,
+            args,
+        )
+    }
+}
@@ -6,7 +6,11 @@ use nom::{
 };
 
 use crate::{
-    interpreter::runtime::{error::Error, state::DefineType, value::Value},
+    interpreter::runtime::{
+        error::{Error, Location},
+        state::DefineType,
+        value::Value,
+    },
     runtime::state::FunctionState,
 };
 use crate::{parsers::*, Interpreter};
@@ -19,6 +23,7 @@ use super::{expression::FunctionExpr, parsers::AstParseResult};
 pub struct FunctionCall {
     name: String,
     args: Vec<Expression>,
+    location: Location,
 }
 
 impl FunctionCall {
@@ -29,7 +34,9 @@ impl FunctionCall {
             args.push(arg.eval(eval_args)?);
         }
 
-        interpreter.state.invoke_func(eval_args, &self.name, args)
+        interpreter
+            .state
+            .invoke_func(eval_args, &self.name, self.location, args)
     }
 
     fn try_get_func<'a, 'b, P, PO>(
@@ -88,6 +95,8 @@ impl FunctionCall {
     where
         P: Parser<PosWithInfo<'a>, (), ()> + Clone,
     {
+        let location = input.into();
+
         // function call syntax
         // - `func_name!`
         // with args
@@ -105,7 +114,7 @@ impl FunctionCall {
         let (input, (identifier, func)) = if let Ok(res) = strict_result {
             res
         } else {
-            let relaxed_result = match identifier_term {
+            let relaxed_result = match identifier_term.clone() {
                 Some(identifier_term) => {
                     Self::try_get_func(input, identifier_term, fail_if_lower_identifier_order)
                 }
@@ -115,20 +124,47 @@ impl FunctionCall {
                 .map_err(|_| nom::Err::Error(nom::error::Error::new(input, ErrorKind::Fail)))?
         };
 
+        let no_args = Self {
+            name: identifier.to_string(),
+            args: Vec::new(),
+            location,
+        };
+
         // no args?
-        let arg_count = func.arg_count.unwrap_or_default();
-        if arg_count == 0 {
-            // no args
-            return Ok((
-                input,
-                Self {
-                    name: identifier.to_string(),
-                    args: Vec::new(),
-                },
-            ));
+        if func.arg_count == Some(0) {
+            return Ok((input, no_args));
+        }
+
+        // `()` calls it without its arguments if they are optional, since the parentheses are whitespace otherwise
+        let mut empty_parens = tuple((char::<_, ()>('('), space0, char(')')));
+        if func.required_args == 0 && empty_parens.parse(input).is_ok() {
+            return Ok((input, no_args));
         }
 
-        // has args
+        // `func.property` is a property of the function rather than a call with args
+        if let Some(mut identifier_term) = identifier_term {
+            if identifier_term.parse(input).is_ok() {
+                return Err(nom::Err::Error(nom::error::Error::new(
+                    input,
+                    ErrorKind::Fail,
+                )));
+            }
+        }
+
+        match Self::parse_args(input, func.required_args, func.arg_count) {
+            Ok((input, args)) => Ok((input, Self { args, ..no_args })),
+            Err(_) if func.required_args == 0 => Ok((input, no_args)),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Parses the arguments of a call to a function with `arg_count` arguments, or any number of them if it's `None`
+    /// - Arguments after the first `required` ones can be left out
+    fn parse_args(
+        input: PosWithInfo,
+        required: usize,
+        arg_count: Option<usize>,
+    ) -> AstParseResult<Vec<Expression>> {
         let (input, _) = tuple((not(end_of_statement), ws))(input)?;
 
         let (mut input, mut args) = {
@@ -137,21 +173,19 @@ impl FunctionCall {
         };
 
         // grab arguments
-        for _ in 0..arg_count - 1 {
+        while arg_count.is_none_or(|arg_count| args.len() < arg_count) {
             // TODO for expression, implement some way to either make the expression parse until the end of the statement or stringify the expression
             let (input_new, (_, _, expr, _)) =
-                tuple((char(','), ws, Expression::parse, ws))(input)?;
+                match tuple((char(','), ws, Expression::parse, ws))(input) {
+                    Ok(res) => res,
+                    Err(_) if args.len() >= required => break,
+                    Err(err) => return Err(err),
+                };
             input = input_new;
             args.push(expr);
         }
 
-        Ok((
-            input,
-            Self {
-                name: identifier.to_string(),
-                args,
-            },
-        ))
+        Ok((input, args))
     }
 }
 
@@ -159,13 +193,17 @@ impl FunctionCall {
 /// A function definition
 pub struct FunctionDef {
     pub name: String,
-    func: FunctionExpr,
+    pub func: FunctionExpr,
 }
 
 const FUNCTION_HEADER: &[char] = &['f', 'u', 'n', 'c', 't', 'i', 'o', 'n'];
 
 impl FunctionDef {
     pub fn parse(input: PosWithInfo) -> AstParseResult<Self> {
+        let (input, is_async) = opt(tuple((tag("async"), ws1)))
+            .map(|is_async| is_async.is_some())
+            .parse(input)?;
+
         // header
         let (input, first_ch) = satisfy(|c| !is_ws(c))(input)?;
         let header_start_index = FUNCTION_HEADER.iter().position(|c| *c == first_ch);
@@ -192,7 +230,8 @@ impl FunctionDef {
 
         let (input, (_, identifier, _)) = tuple((ws, identifier, ws))(input)?;
 
-        let (input, expr) = FunctionExpr::parse(input)?;
+        let (input, mut expr) = FunctionExpr::parse(input)?;
+        expr.is_async = is_async;
 
         let instance = Self {
             name: identifier.input.to_string(),
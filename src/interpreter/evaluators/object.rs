@@ -44,7 +44,8 @@ impl ObjectInitialiser {
         for (key, value) in self.0.iter() {
             obj.insert(key.to_string(), value.eval(eval_args)?.0.into_owned());
         }
-        let obj = Object::new(obj);
+        let state = &eval_args.extra.0.state;
+        let obj = state.heap.alloc(Object::new(&state.realm, obj));
 
         Ok(obj.into())
     }
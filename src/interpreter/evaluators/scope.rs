@@ -10,7 +10,7 @@ use nom::{
 
 use super::{parsers::AstParseResult, statement::Statement};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ScopeStart {
     line: usize,
 }
@@ -31,7 +31,7 @@ impl ScopeStart {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ScopeEnd {
     line: usize,
 }
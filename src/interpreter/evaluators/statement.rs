@@ -3,21 +3,24 @@ use nom::{branch::*, combinator::*, sequence::*, Parser};
 use crate::{
     interpreter::{
         evaluators::{function::FunctionCall, variable::VarSet},
-        runtime::{self, value::Value},
+        runtime::{self, error::Location, value::Value},
     },
     parsers::*,
 };
 
 use super::{
+    class::Class,
     conditional::{If, When},
     control_flow::Reverse,
+    delete::{deleted_keyword, Delete},
+    files::{Export, Import},
     function::{FunctionDef, Return},
     parsers::AstParseResult,
     scope::*,
     variable::VariableDecl,
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Statement {
     FunctionCall(FunctionCall),
     FunctionDef(FunctionDef),
@@ -30,11 +33,17 @@ pub enum Statement {
     If(If),
     When(When),
     Reverse(Reverse),
+    Class(Class),
+    Delete(Delete),
+    Export(Export),
+    Import(Import),
+    /// A statement that uses a deleted keyword
+    Deleted(String),
 }
 
 impl Statement {
     pub fn parse(input: PosWithInfo) -> AstParseResult<Self> {
-        let (mut input, _) = ws::<_, ()>(input).unwrap();
+        let (input, _) = ws::<_, ()>(input).unwrap();
 
         if input.input.is_empty() {
             return Err(nom::Err::Error(nom::error::Error::new(
@@ -43,6 +52,20 @@ impl Statement {
             )));
         }
 
+        // an unterminated block comment would otherwise take the rest of the code as an implicit string
+        if input.input.starts_with("/*") && comment_block::<_, ()>(input).is_err() {
+            return Err(nom::Err::Error(nom::error::Error::new(
+                input,
+                nom::error::ErrorKind::TakeUntil,
+            )));
+        }
+
+        // the rest of the statement is skipped, it fails when evaluated
+        if let Some(keyword) = deleted_keyword(input) {
+            let (input, _) = Self::implicit_string(input);
+            return Ok((input, Self::Deleted(keyword)));
+        }
+
         // this needs to be done here since functions can be recursive
         let function_call = tuple((FunctionCall::parse_as_func, end_of_statement))
             .map(|(func, _)| Statement::FunctionCall(func));
@@ -55,10 +78,18 @@ impl Statement {
         let if_ = If::parse.map(Statement::If);
         let when = When::parse.map(Statement::When);
         let reverse = Reverse::parse.map(Statement::Reverse);
+        let class = Class::parse.map(Statement::Class);
+        let delete = Delete::parse.map(Statement::Delete);
+        let export = Export::parse.map(Statement::Export);
+        let import = Import::parse.map(Statement::Import);
 
         if let Ok((input, statement)) = alt((
             function_call,
             function_def,
+            class,
+            delete,
+            export,
+            import,
             variable_decl,
             var_set,
             reverse,
@@ -74,11 +105,17 @@ impl Statement {
 
         // TODO rewrite test to ensure type isn't implicit string
         // last resort, pass it as an implicit string
+        let (input, implicit_string) = Self::implicit_string(input);
+        Ok((input, Self::ImplicitString(Value::String(implicit_string))))
+    }
+
+    /// Takes the rest of the statement as a string
+    fn implicit_string(mut input: PosWithInfo) -> (PosWithInfo, String) {
         let mut implicit_string = String::new();
 
         loop {
             if let Ok((input, _)) = alt((value((), eof::<_, ()>), end_of_statement))(input) {
-                return Ok((input, Self::ImplicitString(Value::String(implicit_string))));
+                return (input, implicit_string);
             }
 
             if let Ok((input_new, chunk)) = alt((ws1_value, terminated_chunk_value))(input) {
@@ -110,7 +147,12 @@ impl Statement {
                     return_value,
                 });
             }
-            Statement::If(if_) => return if_.eval(args).map(|_| Default::default()),
+            Statement::If(if_) => {
+                return if_.eval(args).map(|return_value| StatementReturn {
+                    value: None,
+                    return_value,
+                });
+            }
             Statement::When(when) => {
                 when.eval(args);
                 return Ok(Default::default());
@@ -119,6 +161,13 @@ impl Statement {
                 reverse.eval(args);
                 return Ok(Default::default());
             }
+            Statement::Class(class) => return class.eval(args).map(|_| Default::default()),
+            Statement::Delete(delete) => return delete.eval(args).map(|_| Default::default()),
+            Statement::Export(export) => return export.eval(args).map(|_| Default::default()),
+            Statement::Import(import) => return import.eval(args).map(|_| Default::default()),
+            Statement::Deleted(keyword) => {
+                return Err(runtime::Error::Deleted(keyword.to_string()))
+            }
         };
 
         Ok(StatementReturn {
@@ -126,6 +175,47 @@ impl Statement {
             return_value: None,
         })
     }
+
+    /// Evaluates the statement, recording where it is if it fails
+    /// - `start` is the input the statement was parsed from
+    pub fn eval_traced(
+        &self,
+        start: PosWithInfo,
+        args: PosWithInfo,
+    ) -> Result<StatementReturn, runtime::error::Error> {
+        self.eval_counted(args, || ws::<_, ()>(start).unwrap().0.into())
+    }
+
+    /// Same as [`Statement::eval_traced`], for a statement whose location is already known
+    pub fn eval_at(
+        &self,
+        location: Location,
+        args: PosWithInfo,
+    ) -> Result<StatementReturn, runtime::error::Error> {
+        self.eval_counted(args, || location)
+    }
+
+    /// Evaluates the statement as a step, where `location` is where it is if it fails
+    fn eval_counted(
+        &self,
+        args: PosWithInfo,
+        location: impl FnOnce() -> Location,
+    ) -> Result<StatementReturn, runtime::error::Error> {
+        let state = &args.extra.0.state;
+        // variables whose lifetime ran out during the statement expire before the next one is parsed
+        let res = state.step().and_then(|_| self.eval(args)).and_then(|ret| {
+            state.expire_timers(args)?;
+            Ok(ret)
+        });
+        match res {
+            // `next` suspends the statement until the variable is assigned
+            Err(runtime::Error::Suspended(name)) => {
+                state.wait_for_next(&name, self.clone());
+                Ok(Default::default())
+            }
+            res => res.inspect_err(|_| state.trace_error(location())),
+        }
+    }
 }
 
 #[derive(Default)]
@@ -0,0 +1,133 @@
+//! Contains template string related structures
+
+use nom::{
+    bytes::complete::{tag, take_while1},
+    character::complete::{anychar, char},
+    combinator::fail,
+    sequence::tuple,
+    IResult, InputTake,
+};
+
+use crate::{
+    parsers::{ws, PosWithInfo},
+    runtime::{
+        value::{string, Hint, Value},
+        Error,
+    },
+};
+
+use super::{expression::Expression, parsers::AstParseResult};
+
+#[derive(Debug, Clone)]
+/// A string with interpolated expressions, like `"Hello ${name}!"`
+/// - The currency symbol of the locale marks the interpolations, it goes before the braces or after them like `"Hello {name}€!"`
+/// - Interpolations are found when parsing, so changing the locale only affects code parsed after it
+pub struct Template(Vec<TemplatePart>);
+
+#[derive(Debug, Clone)]
+enum TemplatePart {
+    Text(String),
+    Expression(Expression),
+}
+
+impl Template {
+    /// Parses a string that has interpolations
+    /// - Fails for any other string, which is left to [`Value::parse`]
+    pub fn parse(input: PosWithInfo) -> AstParseResult<Self> {
+        let locale = input.extra.0.state.locale.lock().unwrap().clone();
+        let currency = locale.currency.as_str();
+
+        let (mut rest, quotes) = take_while1(|c| c == '\'' || c == '"')(input)?;
+        // closing quotes mirror the opening ones
+        let closing = quotes.input.chars().rev().collect::<String>();
+
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut is_template = false;
+        loop {
+            if rest.input.starts_with(['\'', '"']) {
+                let Ok((rest, _)) = tag::<_, _, ()>(closing.as_str())(rest) else {
+                    return fail(input);
+                };
+                if !is_template {
+                    return fail(input);
+                }
+                if !text.is_empty() {
+                    parts.push(TemplatePart::Text(text));
+                }
+                return Ok((rest, Self(parts)));
+            }
+
+            if let Ok((rest_new, expr)) = interpolation(rest, currency, locale.suffix) {
+                if !text.is_empty() {
+                    parts.push(TemplatePart::Text(std::mem::take(&mut text)));
+                }
+                parts.push(TemplatePart::Expression(expr));
+                is_template = true;
+                rest = rest_new;
+                continue;
+            }
+
+            let Ok((rest_new, ch)) = anychar::<_, ()>(rest) else {
+                // not closed
+                return fail(input);
+            };
+            rest = rest_new;
+            if ch != '\\' {
+                text.push(ch);
+                continue;
+            }
+
+            // an escaped currency symbol or brace doesn't start an interpolation
+            let (ch, len) = string::decode_escape(rest.input);
+            text.extend(ch);
+            rest = rest.take_split(len).0;
+        }
+    }
+
+    pub fn eval(&self, args: PosWithInfo) -> Result<Value, Error> {
+        let mut string = String::new();
+        for part in &self.0 {
+            match part {
+                TemplatePart::Text(text) => string.push_str(text),
+                TemplatePart::Expression(expr) => {
+                    let value = expr.eval(args)?.to_primitive(args, Hint::String)?;
+                    string.push_str(&value.coerce_string()?);
+                }
+            }
+        }
+
+        Ok(Value::String(string))
+    }
+}
+
+/// Parses `${expr}`, or `{expr}$` if the currency symbol goes after the braces
+fn interpolation<'a>(
+    input: PosWithInfo<'a>,
+    currency: &str,
+    suffix: bool,
+) -> AstParseResult<'a, Expression> {
+    fn right_brace(input: PosWithInfo) -> IResult<PosWithInfo, PosWithInfo, ()> {
+        tag("}")(input)
+    }
+
+    let input = if suffix {
+        input
+    } else {
+        tag(currency)(input)?.0
+    };
+    let (input, (_, _, expr, _, _)) = tuple((
+        char('{'),
+        ws,
+        Expression::parser(Some(right_brace)),
+        ws,
+        char('}'),
+    ))(input)?;
+    let input = if suffix {
+        tag(currency)(input)?.0
+    } else {
+        input
+    };
+
+    Ok((input, expr))
+}
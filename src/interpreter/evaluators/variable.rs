@@ -5,14 +5,18 @@ use nom::{
     bytes::complete::tag,
     character::complete::*,
     combinator::{fail, opt, value},
-    multi::many1,
+    multi::{many0, many1},
     sequence::{tuple, Tuple},
     Parser,
 };
 use num_bigint::BigInt;
 use num_traits::FromPrimitive;
 
-use crate::{interpreter::runtime::error::Error, parsers::*, runtime::value::Value};
+use crate::{
+    interpreter::runtime::error::Error,
+    parsers::*,
+    runtime::value::{Hint, Value},
+};
 
 use super::{
     expression::{AtomPostfix, Expression},
@@ -22,33 +26,98 @@ use super::{
 #[derive(Debug, Clone)]
 /// Declared variable
 pub struct VariableDecl {
-    name: String,
+    pub name: DeclName,
     pub expression: Expression,
     line: usize,
     type_: VarType,
     life_time: Option<LifeTime>,
 }
 
+#[derive(Debug, Clone)]
+/// What a declaration declares
+pub enum DeclName {
+    Identifier(String),
+    /// `[a, b]`, which declares the elements of an array, starting from index -1
+    Array(Vec<String>),
+}
+
+impl DeclName {
+    pub fn names(&self) -> &[String] {
+        match self {
+            DeclName::Identifier(name) => std::slice::from_ref(name),
+            DeclName::Array(names) => names,
+        }
+    }
+
+    fn parse(input: PosWithInfo) -> AstParseResult<Self> {
+        let element = || identifier(alt((char::<PosWithInfo, _>(','), char(']'))));
+        let elements = tuple((
+            element(),
+            many0(tuple((ws, char(','), ws, element())).map(|(_, _, _, element)| element)),
+        ))
+        .map(|(first, mut rest)| {
+            rest.insert(0, first);
+            rest.into_iter()
+                .map(|element| element.input.to_string())
+                .collect()
+        });
+        let array = tuple((char('['), ws, elements, ws, char(']')))
+            .map(|(_, _, elements, _, _)| DeclName::Array(elements));
+        let identifier = identifier(LifeTime::parse)
+            .map(|identifier| DeclName::Identifier(identifier.to_string()));
+
+        alt((array, identifier))(input)
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum VarType {
     VarVar,
     ConstVar,
     VarConst,
     ConstConst,
+    /// Constant across every interpreter sharing the store, see [`store`](crate::interpreter::runtime::store)
+    ConstConstConst,
 }
 
 impl VariableDecl {
     pub fn eval(&self, args: PosWithInfo) -> Result<(), Error> {
         let interpreter = args.extra.0;
-        let value = self.expression.eval(args)?;
-        interpreter.state.add_var_runtime(
-            &self.name,
-            value.0.into_owned(),
-            self.line,
-            self.type_,
-            self.life_time,
-            args,
-        )?;
+        let value = self.expression.eval(args)?.0.into_owned();
+        let values = match &self.name {
+            DeclName::Identifier(name) => vec![(name, value)],
+            DeclName::Array(names) => {
+                let Value::Object(Some(obj)) = value else {
+                    return Err(Error::Type(format!("{value} is not an array")));
+                };
+                let obj = obj.lock().unwrap();
+                names
+                    .iter()
+                    .zip(-1..)
+                    .map(|(name, i)| {
+                        let value = obj.get_property(&i.to_string());
+                        (name, value.unwrap_or(Value::Undefined))
+                    })
+                    .collect()
+            }
+        };
+
+        for (name, value) in values {
+            if matches!(self.type_, VarType::ConstConstConst) {
+                interpreter
+                    .state
+                    .declare_const_const_const(name, value, args)?;
+                continue;
+            }
+            interpreter.state.add_var_runtime(
+                name,
+                value,
+                self.line,
+                self.type_,
+                self.life_time,
+                args,
+            )?;
+        }
 
         Ok(())
     }
@@ -57,20 +126,27 @@ impl VariableDecl {
         let var = || tag("var");
         let const_ = || tag("const");
         let eq = char('=');
+        let const_const_const =
+            tuple((const_(), ws1, const_(), ws1, const_())).map(|_| VarType::ConstConstConst);
         let const_const = tuple((const_(), ws1, const_())).map(|_| VarType::ConstConst);
         let const_var = tuple((const_(), ws1, var())).map(|_| VarType::ConstVar);
         let var_const = tuple((var(), ws1, const_())).map(|_| VarType::VarConst);
         let var_var = tuple((var(), ws1, var())).map(|_| VarType::VarVar);
-        let type_ = alt((const_const, var_var, const_var, var_const));
-        let identifier = identifier(LifeTime::parse);
+        let type_ = alt((
+            const_const_const,
+            const_const,
+            var_var,
+            const_var,
+            var_const,
+        ));
 
         let line = input.line;
 
         // var ws+ var ws+ identifier life_time? ws* "=" ws* expr
-        let (input, (type_, _, identifier, life_time, _, _, _, expression, _)) = (
+        let (input, (type_, _, name, life_time, _, _, _, expression, _)) = (
             type_,
             ws1,
-            identifier,
+            DeclName::parse,
             opt(LifeTime::parse),
             ws,
             eq,
@@ -82,7 +158,7 @@ impl VariableDecl {
 
         let decl = Self {
             expression,
-            name: identifier.input.to_string(),
+            name,
             line,
             type_,
             life_time,
@@ -125,6 +201,10 @@ impl VarSet {
     // TODO: prefix operation
     pub fn eval(&self, args: PosWithInfo) -> Result<(), Error> {
         let interpreter = args.extra.0;
+        // compound assignments are checked before their operands are evaluated too
+        interpreter
+            .state
+            .check_write(&self.name, !self.postfix.is_empty())?;
 
         let expr = self
             .expression
@@ -139,41 +219,37 @@ impl VarSet {
                 .to_owned()
         };
 
+        // objects are converted to primitives for arithmetic
+        let hint = match self.op {
+            VarSetOp::Add => Hint::Default,
+            _ => Hint::Number,
+        };
+        let operand = |value: Value| value.to_primitive(args, hint);
+        // `++` and `--` convert strings to numbers instead of concatenating
+        let numeric = |value: Value| match value {
+            Value::BigInt(_) => Ok(value),
+            value => f64::try_from(value).map(Value::Number),
+        };
+
         let value = match self.op {
             VarSetOp::Equals => expr.unwrap()?,
             VarSetOp::And => Value::Boolean(value().into() && expr.unwrap()?.into()),
             VarSetOp::Or => Value::Boolean(value().into() || expr.unwrap()?.into()),
-            VarSetOp::Add => (value() + expr.unwrap()?)?,
-            VarSetOp::Subtract => (value() - expr.unwrap()?)?,
-            VarSetOp::Multiply => (value() * expr.unwrap()?)?,
-            VarSetOp::Exponential => value().pow(&expr.unwrap()?)?,
-            VarSetOp::Divide => (value() / expr.unwrap()?)?,
-            VarSetOp::Modulo => (value() % expr.unwrap()?)?,
+            VarSetOp::Add => (operand(value())? + operand(expr.unwrap()?)?)?,
+            VarSetOp::Subtract => (operand(value())? - operand(expr.unwrap()?)?)?,
+            VarSetOp::Multiply => (operand(value())? * operand(expr.unwrap()?)?)?,
+            VarSetOp::Exponential => operand(value())?.pow(&operand(expr.unwrap()?)?)?,
+            VarSetOp::Divide => (operand(value())? / operand(expr.unwrap()?)?)?,
+            VarSetOp::Modulo => (operand(value())? % operand(expr.unwrap()?)?)?,
             VarSetOp::AddOne => {
-                let value = value();
-                match value {
-                    Value::Number(value) => Value::Number(value + 1.0),
-                    Value::BigInt(value) => Value::BigInt(value + BigInt::from_u8(1).unwrap()),
-                    _ => {
-                        // TODO: is this behaviour accurate?
-                        // TODO: error handle
-                        let value = f64::try_from(value).unwrap();
-                        Value::Number(value + 1.0)
-                    }
-                }
+                let value = numeric(operand(value())?)?;
+                let one = Self::one(&value);
+                (value + one)?
             }
             VarSetOp::SubOne => {
-                let value = value();
-                match value {
-                    Value::Number(value) => Value::Number(value - 1.0),
-                    Value::BigInt(value) => Value::BigInt(value - BigInt::from_u8(1).unwrap()),
-                    _ => {
-                        // TODO: is this behaviour accurate?
-                        // TODO: error handle
-                        let value = f64::try_from(value).unwrap();
-                        Value::Number(value - 1.0)
-                    }
-                }
+                let value = numeric(operand(value())?)?;
+                let one = Self::one(&value);
+                (value - one)?
             }
         };
 
@@ -184,6 +260,14 @@ impl VarSet {
         Ok(())
     }
 
+    /// The `1` that `++` and `--` add to or subtract from `value`, which is a BigInt for a BigInt
+    fn one(value: &Value) -> Value {
+        match value {
+            Value::BigInt(_) => Value::BigInt(BigInt::from_u8(1).unwrap()),
+            _ => Value::Number(1.0),
+        }
+    }
+
     pub fn parse(input_orig: PosWithInfo) -> AstParseResult<Self> {
         // TODO: do i need lifetime check here
         let mut identifier_full = identifier(alt((
@@ -56,6 +56,16 @@ fn slice_from() {
     assert_eq!(input.index, 3);
 }
 
+#[test]
+fn slice_from_new_line() {
+    let input = Position::new("foo\n  bar");
+    let input = input.slice(6..);
+    assert_eq!(input.input, "bar");
+    assert_eq!(input.line, 2);
+    assert_eq!(input.column, 3);
+    assert_eq!(input.index, 6);
+}
+
 #[test]
 fn calc_line_column_test() {
     let input = "foo bar\nbaz";
@@ -236,3 +246,16 @@ fn take_until_parser_() {
     assert_eq!(res, "foo ");
     assert_eq!(input, "bar");
 }
+
+#[test]
+fn split_at_position_to_end() {
+    let input = Position::new("foo");
+    let (rest, taken) = input
+        .split_at_position_complete::<_, ()>(|c| c == '!')
+        .unwrap();
+
+    assert_eq!(taken.input, "foo");
+    assert_eq!(rest.input, "");
+    assert_eq!(rest.column, 4);
+    assert_eq!(rest.index, 3);
+}
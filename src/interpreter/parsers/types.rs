@@ -136,7 +136,7 @@ where
 {
     fn take(&self, count: usize) -> Self {
         let mut new = *self;
-        new.input = &self.input.take(count);
+        new.input = self.input.take(count);
         new
     }
 
@@ -236,7 +236,7 @@ impl<T> InputTakeAtPosition for Position<'_, T> {
             None => self.left_right_split(
                 &self.input[..self.input.len()],
                 &self.input[self.input.len()..],
-                0,
+                self.input.len(),
             ),
         };
 
@@ -263,7 +263,7 @@ impl<T> InputTakeAtPosition for Position<'_, T> {
                     self.left_right_split(
                         &self.input[..self.input.len()],
                         &self.input[self.input.len()..],
-                        0,
+                        self.input.len(),
                     )
                 }
             }
@@ -282,15 +282,7 @@ where
             self.input.slice(..range.start),
             self.input.slice(range.start..),
         );
-        let (line, column) = calc_line_column(left);
-
-        Self {
-            line: self.line + line,
-            column: self.column + column,
-            index: self.index + left.input_len(),
-            input: right,
-            extra: self.extra,
-        }
+        self.left_right_split(left, right, left.input_len()).1
     }
 }
 
@@ -313,23 +305,17 @@ where
 
 impl<'input, T, I: ?Sized> Slice<Range<usize>> for Position<'input, T, I>
 where
-    &'input I: Slice<Range<usize>> + Slice<RangeFrom<usize>> + AsChars + InputLength + 'input,
+    &'input I: Slice<Range<usize>>
+        + Slice<RangeTo<usize>>
+        + Slice<RangeFrom<usize>>
+        + AsChars
+        + InputLength
+        + 'input,
 {
     fn slice(&self, range: Range<usize>) -> Self {
-        // position would be left + right
-        let (left, right) = (
-            self.input.slice(range.clone()),
-            self.input.slice(range.end..),
-        );
-        let (line, column) = calc_line_column(right);
-
-        Self {
-            line: self.line - line,
-            column: self.column - column,
-            index: self.index - right.input_len(),
-            input: left,
-            extra: self.extra,
-        }
+        let mut pos = self.slice(range.start..);
+        pos.input = self.input.slice(range);
+        pos
     }
 }
 
@@ -430,6 +416,12 @@ where
     /// Splits the input into two positions
     fn left_right_split(&self, left: &'input I, right: &'input I, len: usize) -> (Self, Self) {
         let (line, column) = calc_line_column(left);
+        // the column restarts if there is a new line
+        let column = if line == 0 {
+            self.column + column
+        } else {
+            column + 1
+        };
         (
             Self {
                 line: self.line,
@@ -440,7 +432,7 @@ where
             },
             Self {
                 line: self.line + line,
-                column: self.column + column,
+                column,
                 index: self.index + len,
                 input: right,
                 extra: self.extra,
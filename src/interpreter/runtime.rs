@@ -1,6 +1,14 @@
-pub(crate) mod error;
+pub mod budget;
+pub mod clock;
+pub mod error;
+pub mod files;
+pub mod heap;
+pub mod io;
+pub mod locale;
+pub mod realm;
 pub(crate) mod state;
 pub(crate) mod stdlib;
+pub mod store;
 pub mod value;
 
-pub(crate) use error::Error;
+pub use error::Error;
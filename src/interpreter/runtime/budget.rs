@@ -0,0 +1,144 @@
+//! Limits on how much the code can do, so a runaway program can be stopped
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Instant,
+};
+
+use super::Error;
+
+#[derive(Debug, Clone, Copy, Default)]
+/// Limits for evaluating code, where `None` is no limit
+pub struct Limits {
+    /// Maximum number of statements executed by each `eval`
+    pub max_steps: Option<u64>,
+    /// Time after which evaluating stops
+    pub deadline: Option<Instant>,
+    /// Maximum number of function calls inside each other
+    pub max_call_depth: Option<usize>,
+    /// Maximum number of `when` bodies triggered inside each other
+    pub max_when_depth: Option<usize>,
+    /// Maximum number of objects that are alive
+    pub max_objects: Option<usize>,
+    /// Maximum number of properties of all objects that are alive
+    pub max_properties: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default)]
+/// Stops evaluating from any thread
+pub struct CancelHandle(Arc<AtomicBool>);
+
+impl CancelHandle {
+    /// Stops evaluating at the next statement or function call
+    /// - Evaluating keeps failing until [`CancelHandle::reset`] is called
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn reset(&self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug, Default)]
+/// Keeps track of how much of the limits is used up
+pub struct Budget {
+    pub limits: Mutex<Limits>,
+    pub cancel: CancelHandle,
+    steps: AtomicU64,
+    when_depth: AtomicUsize,
+}
+
+impl Budget {
+    /// Starts counting statements from zero, which is done for each `eval`
+    pub fn reset_steps(&self) {
+        self.steps.store(0, Ordering::Relaxed);
+    }
+
+    /// Counts a statement that is about to be executed
+    pub fn step(&self) -> Result<(), Error> {
+        let limits = *self.limits.lock().unwrap();
+        let steps = self.steps.fetch_add(1, Ordering::Relaxed) + 1;
+        if let Some(max_steps) = limits.max_steps {
+            if steps > max_steps {
+                return Err(Error::StepLimit(max_steps));
+            }
+        }
+
+        self.check(&limits)
+    }
+
+    /// Checks if cancelling or the deadline stops evaluating, for work that isn't a statement or a call
+    pub fn poll(&self) -> Result<(), Error> {
+        let limits = *self.limits.lock().unwrap();
+        self.check(&limits)
+    }
+
+    /// Checks if a function call can be made
+    /// - `depth` is the number of calls including the new one
+    pub fn call(&self, depth: usize) -> Result<(), Error> {
+        let limits = *self.limits.lock().unwrap();
+        if let Some(max_call_depth) = limits.max_call_depth {
+            if depth > max_call_depth {
+                return Err(Error::CallDepth(max_call_depth));
+            }
+        }
+
+        self.check(&limits)
+    }
+
+    /// Counts a `when` body that is about to be executed, until the guard is dropped
+    pub fn when(&self) -> Result<WhenDepthGuard<'_>, Error> {
+        let limits = *self.limits.lock().unwrap();
+        let guard = WhenDepthGuard(&self.when_depth);
+        let depth = self.when_depth.fetch_add(1, Ordering::Relaxed) + 1;
+        if let Some(max_when_depth) = limits.max_when_depth {
+            if depth > max_when_depth {
+                return Err(Error::WhenDepth(max_when_depth));
+            }
+        }
+
+        Ok(guard)
+    }
+
+    /// Number of `when` bodies being executed inside each other
+    pub fn when_depth(&self) -> usize {
+        self.when_depth.load(Ordering::Relaxed)
+    }
+
+    /// Sets how many `when` bodies the next one counts as being inside of
+    pub fn set_when_depth(&self, depth: usize) {
+        self.when_depth.store(depth, Ordering::Relaxed);
+    }
+
+    /// Checks the limits that don't depend on what is executed
+    fn check(&self, limits: &Limits) -> Result<(), Error> {
+        if self.cancel.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+
+        if let Some(deadline) = limits.deadline {
+            if Instant::now() >= deadline {
+                return Err(Error::Deadline);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Decrements the `when` depth when dropped
+pub struct WhenDepthGuard<'a>(&'a AtomicUsize);
+
+impl Drop for WhenDepthGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
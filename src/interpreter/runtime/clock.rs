@@ -0,0 +1,254 @@
+//! Time that lifetimes in seconds and `sleep` are measured with
+//!
+//! By default this is the system's monotonic clock, but anything that implements [`Clock`] can be installed with
+//! [`Interpreter::with_clock`](crate::Interpreter::with_clock) or [`Interpreter::set_clock`](crate::Interpreter::set_clock),
+//! such as a [`VirtualClock`] that only moves when it's told to
+//!
+//! Variables that expire are kept in a timer wheel, so they expire at the first statement after their time is up
+//! without every lookup checking the time
+
+use std::{
+    fmt::Debug,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Source of the current time
+pub trait Clock: Send {
+    /// Time since a fixed point, which never goes backwards
+    fn now(&self) -> Duration;
+
+    /// Waits for the duration, which is how `sleep` passes time
+    fn sleep(&mut self, duration: Duration);
+}
+
+/// Uses the system's monotonic clock, starting from when it was created
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    fn sleep(&mut self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
+
+/// Clock that only moves with [`VirtualClock::advance`] or `sleep`, which returns at once
+/// - Cloning this shares the same time, so a clone can be kept to move the time of the interpreter it's installed on
+#[derive(Debug, Default, Clone)]
+pub struct VirtualClock(Arc<Mutex<Duration>>);
+
+impl VirtualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Moves the time forward
+    /// - Variables that expired by then expire at the next statement that is evaluated
+    pub fn advance(&self, duration: Duration) {
+        *self.0.lock().unwrap() += duration;
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> Duration {
+        *self.0.lock().unwrap()
+    }
+
+    fn sleep(&mut self, duration: Duration) {
+        self.advance(duration);
+    }
+}
+
+pub type TimerId = u64;
+
+#[derive(Debug, Clone)]
+/// A variable that expires at the deadline
+pub(crate) struct Timer {
+    pub id: TimerId,
+    pub name: String,
+    pub deadline: Duration,
+}
+
+/// Time that each slot of the wheel covers
+const TICK: Duration = Duration::from_millis(1);
+/// Number of slots of the wheel, where timers further away than this many ticks share slots with closer ones
+const SLOTS: u64 = 256;
+
+#[derive(Debug)]
+/// Hashed timer wheel, where each timer is in the slot of the tick its deadline is in
+/// - Expiring only looks at the slots of the ticks that passed, instead of every timer
+struct TimerWheel {
+    slots: Vec<Vec<Timer>>,
+    /// Last tick whose slot was looked at
+    current: u64,
+    len: usize,
+}
+
+impl TimerWheel {
+    fn new(now: Duration) -> Self {
+        Self {
+            slots: vec![Vec::new(); SLOTS as usize],
+            current: Self::tick(now),
+            len: 0,
+        }
+    }
+
+    fn tick(time: Duration) -> u64 {
+        (time.as_nanos() / TICK.as_nanos()) as u64
+    }
+
+    fn insert(&mut self, timer: Timer) {
+        // the first tick that is at or after the deadline, and hasn't been looked at yet
+        let tick =
+            (timer.deadline.as_nanos().div_ceil(TICK.as_nanos()) as u64).max(self.current + 1);
+        self.slots[(tick % SLOTS) as usize].push(timer);
+        self.len += 1;
+    }
+
+    /// Takes the timers whose deadline is at or before `now`, in the order of their deadlines
+    fn expire(&mut self, now: Duration) -> Vec<Timer> {
+        let now_tick = Self::tick(now);
+        let mut expired = Vec::new();
+        if self.len == 0 {
+            self.current = self.current.max(now_tick);
+            return expired;
+        }
+
+        let mut take = |slot: &mut Vec<Timer>| {
+            let (due, rest): (Vec<_>, Vec<_>) = std::mem::take(slot)
+                .into_iter()
+                .partition(|timer| timer.deadline <= now);
+            *slot = rest;
+            expired.extend(due);
+        };
+
+        let passed = now_tick.saturating_sub(self.current).min(SLOTS);
+        for tick in self.current + 1..=self.current + passed {
+            take(&mut self.slots[(tick % SLOTS) as usize]);
+        }
+        self.current = self.current.max(now_tick);
+        // the tick that `now` is in hasn't passed yet, but the timers before `now` in it are due
+        take(&mut self.slots[((self.current + 1) % SLOTS) as usize]);
+
+        self.len -= expired.len();
+        expired.sort_by_key(|timer| (timer.deadline, timer.id));
+        expired
+    }
+
+    fn next_deadline(&self) -> Option<Duration> {
+        self.slots
+            .iter()
+            .flatten()
+            .map(|timer| timer.deadline)
+            .min()
+    }
+
+    fn drain(&mut self) -> Vec<Timer> {
+        self.len = 0;
+        self.slots.iter_mut().flat_map(std::mem::take).collect()
+    }
+}
+
+/// The [`Clock`] that is installed on an interpreter, along with the variables waiting to expire
+pub(crate) struct Timers {
+    clock: Box<dyn Clock>,
+    wheel: TimerWheel,
+    next_id: TimerId,
+}
+
+impl Timers {
+    pub fn new(clock: Box<dyn Clock>) -> Self {
+        Self {
+            wheel: TimerWheel::new(clock.now()),
+            clock,
+            next_id: 0,
+        }
+    }
+
+    pub fn now(&self) -> Duration {
+        self.clock.now()
+    }
+
+    /// Starts a timer for the variable that expires after the seconds
+    /// # Returns
+    /// - `None` if the variable never expires, such as when the seconds are infinite
+    pub fn schedule(&mut self, name: &str, seconds: f64) -> Option<TimerId> {
+        let duration = Duration::try_from_secs_f64(seconds.max(0.)).ok()?;
+        let deadline = self.now().checked_add(duration)?;
+
+        let id = self.next_id;
+        self.next_id += 1;
+        self.wheel.insert(Timer {
+            id,
+            name: name.to_string(),
+            deadline,
+        });
+        Some(id)
+    }
+
+    /// Takes the timers that are up
+    pub fn expired(&mut self) -> Vec<Timer> {
+        // most code doesn't have lifetimes in seconds, which doesn't need the time
+        if self.wheel.len == 0 {
+            return Vec::new();
+        }
+        self.wheel.expire(self.clock.now())
+    }
+
+    pub fn next_deadline(&self) -> Option<Duration> {
+        self.wheel.next_deadline()
+    }
+
+    pub fn sleep(&mut self, duration: Duration) {
+        self.clock.sleep(duration);
+    }
+
+    /// Replaces the clock, keeping the time left of each timer
+    pub fn set_clock(&mut self, clock: Box<dyn Clock>) {
+        let old_now = self.clock.now();
+        let new_now = clock.now();
+        let timers = self.wheel.drain();
+
+        self.clock = clock;
+        self.wheel = TimerWheel::new(new_now);
+        for timer in timers {
+            let deadline = new_now + timer.deadline.saturating_sub(old_now);
+            self.wheel.insert(Timer { deadline, ..timer });
+        }
+    }
+}
+
+impl Default for Timers {
+    fn default() -> Self {
+        Self::new(Box::new(SystemClock::new()))
+    }
+}
+
+impl Debug for Timers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Timers")
+            .field("wheel", &self.wheel)
+            .finish_non_exhaustive()
+    }
+}
@@ -1,5 +1,9 @@
+use std::fmt::Display;
+
 use thiserror::Error;
 
+use crate::parsers::types::Position;
+
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("Function {0} not found")]
@@ -10,4 +14,87 @@ pub enum Error {
     RuntimeException(String),
     #[error("TypeError: {0}")]
     Type(String),
+    #[error("RangeError: {0}")]
+    Range(String),
+    #[error("Can't have more than one '{0}' instance")]
+    MultipleInstances(String),
+    #[error("'{0}' wasn't exported to this file")]
+    NotExported(String),
+    /// Assigning to a variable that can't be reassigned, in strict mode
+    #[error("Assignment to constant variable '{0}'")]
+    ConstAssignment(String),
+    /// Setting a property of a value that can't be mutated, in strict mode
+    #[error("Cannot mutate the constant value of '{0}'")]
+    ConstMutation(String),
+    #[error("'{0}' is already declared as const const const")]
+    Redeclared(String),
+    #[error("{0} has been deleted")]
+    Deleted(String),
+    #[error("Exceeded the limit of {0} statements")]
+    StepLimit(u64),
+    #[error("Exceeded the deadline")]
+    Deadline,
+    #[error("Exceeded the maximum call depth of {0}")]
+    CallDepth(usize),
+    #[error("Exceeded the maximum `when` depth of {0}")]
+    WhenDepth(usize),
+    #[error("Exceeded the heap limit of {0} objects")]
+    ObjectLimit(usize),
+    #[error("Exceeded the heap limit of {0} properties")]
+    PropertyLimit(usize),
+    #[error("Evaluation was cancelled")]
+    Cancelled,
+    /// Reading `next` of a variable before it's assigned, which suspends the statement
+    #[error("'next {0}' can only be waited for by a statement")]
+    Suspended(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A line and column in the code
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl<T> From<Position<'_, T>> for Location {
+    fn from(value: Position<'_, T>) -> Self {
+        Self {
+            line: value.line,
+            column: value.column,
+        }
+    }
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A function call that was in progress
+pub struct StackFrame {
+    /// Name the function was called with
+    pub name: String,
+    /// Where the function was called from
+    pub call_site: Location,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Where a runtime error happened
+pub struct Trace {
+    /// Location of the statement that failed
+    pub location: Location,
+    /// Function calls that lead to the error, the innermost call is last
+    pub stack: Vec<StackFrame>,
+}
+
+impl Display for Trace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "at {}", self.location)?;
+        for frame in self.stack.iter().rev() {
+            write!(f, "\nin `{}` called at {}", frame.name, frame.call_site)?;
+        }
+        Ok(())
+    }
 }
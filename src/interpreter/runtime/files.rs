@@ -0,0 +1,92 @@
+//! Programs can be split into multiple files, each evaluated in its own scope
+//! - `=====` lines separate files, and can name the next file like `======= add.db ==`
+//! - Bindings are moved between files with `export x to "add.db"!` and `import x!`
+
+use std::collections::HashMap;
+
+use super::value::Value;
+
+/// Minimum number of `=` for a line to be a file separator
+const SEPARATOR_LEN: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A file in the source code
+pub struct File<'a> {
+    /// Name from the separator, `None` if it's not named or is the code before any separator
+    pub name: Option<&'a str>,
+    pub code: &'a str,
+    /// Line of the source code the file starts at
+    pub line: usize,
+}
+
+/// Gets the file name if the line is a file separator
+fn separator(line: &str) -> Option<Option<&str>> {
+    let line = line.trim();
+    let name = line.trim_start_matches('=');
+    if line.len() - name.len() < SEPARATOR_LEN {
+        return None;
+    }
+
+    let name = name.trim_end_matches('=').trim();
+    Some((!name.is_empty()).then_some(name))
+}
+
+/// Splits the source code into files
+/// - The first file is the code before any separator, which can be empty
+pub fn split(code: &str) -> Vec<File<'_>> {
+    let mut files = Vec::new();
+    let mut file = File {
+        name: None,
+        code,
+        line: 1,
+    };
+    let mut start = 0;
+    let mut index = 0;
+
+    for (i, line) in code.split_inclusive('\n').enumerate() {
+        let line_start = index;
+        index += line.len();
+
+        let Some(name) = separator(line) else {
+            continue;
+        };
+
+        file.code = &code[start..line_start];
+        files.push(file);
+        file = File {
+            name,
+            code,
+            line: i + 2,
+        };
+        start = index;
+    }
+
+    file.code = &code[start..];
+    files.push(file);
+    files
+}
+
+#[derive(Debug, Default)]
+/// The file being evaluated, along with what other files exported to each file
+pub struct Files {
+    current: Option<String>,
+    exports: HashMap<String, HashMap<String, Value>>,
+}
+
+impl Files {
+    pub fn enter(&mut self, name: Option<&str>) {
+        self.current = name.map(str::to_string);
+    }
+
+    pub fn export(&mut self, file: &str, name: &str, value: Value) {
+        self.exports
+            .entry(file.to_string())
+            .or_default()
+            .insert(name.to_string(), value);
+    }
+
+    /// Gets what was exported to the current file with the name
+    pub fn import(&self, name: &str) -> Option<Value> {
+        self.exports.get(self.current.as_ref()?)?.get(name).cloned()
+    }
+}
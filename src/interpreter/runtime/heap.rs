@@ -0,0 +1,165 @@
+//! Objects created by the code, so objects only kept alive by reference cycles can be freed,
+//! and so the number of objects can be limited
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, Weak,
+    },
+};
+
+use super::{
+    budget::Limits,
+    value::{Object, ObjectRef, Value},
+    Error,
+};
+
+/// Number of objects at which collecting starts
+const MIN_THRESHOLD: usize = 1024;
+
+#[derive(Debug)]
+pub struct Heap {
+    objects: Mutex<Vec<Weak<Mutex<Object>>>>,
+    /// Number of properties of all objects on the heap
+    properties: Arc<AtomicUsize>,
+    /// Number of objects at which the next collection happens
+    threshold: AtomicUsize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeapStats {
+    /// Objects that are still alive
+    pub objects: usize,
+    /// Properties of the objects that are still alive
+    pub properties: usize,
+}
+
+impl Default for Heap {
+    fn default() -> Self {
+        Self {
+            objects: Mutex::new(Vec::new()),
+            properties: Arc::new(AtomicUsize::new(0)),
+            threshold: AtomicUsize::new(MIN_THRESHOLD),
+        }
+    }
+}
+
+impl Heap {
+    /// Puts the object on the heap
+    pub fn alloc(&self, mut obj: Object) -> ObjectRef {
+        obj.count_properties(&self.properties);
+        let obj = Arc::new(Mutex::new(obj));
+        self.objects.lock().unwrap().push(Arc::downgrade(&obj));
+        obj
+    }
+
+    pub fn stats(&self) -> HeapStats {
+        let mut objects = self.objects.lock().unwrap();
+        objects.retain(|obj| obj.strong_count() > 0);
+
+        HeapStats {
+            objects: objects.len(),
+            properties: self.properties.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Frees objects that can only be reached from each other
+    /// - References that are not from objects on the heap, like variables and values being evaluated, keep objects alive
+    /// - Objects that are locked keep everything they refer to alive, since their properties can't be read
+    /// # Returns
+    /// - Number of objects that were freed
+    pub fn collect(&self) -> usize {
+        let mut registered = self.objects.lock().unwrap();
+        registered.retain(|obj| obj.strong_count() > 0);
+        let objects = registered
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect::<Vec<_>>();
+        drop(registered);
+
+        let index = objects
+            .iter()
+            .enumerate()
+            .map(|(i, obj)| (Arc::as_ptr(obj), i))
+            .collect::<HashMap<_, _>>();
+
+        // references from outside the heap, not counting the one in `objects`
+        let mut external = objects
+            .iter()
+            .map(|obj| Arc::strong_count(obj) - 1)
+            .collect::<Vec<_>>();
+        let mut children = vec![Vec::new(); objects.len()];
+        let mut locked = vec![false; objects.len()];
+        for (i, obj) in objects.iter().enumerate() {
+            let Ok(obj) = obj.try_lock() else {
+                locked[i] = true;
+                continue;
+            };
+            for value in obj.values() {
+                if let Value::Object(Some(child)) = value {
+                    if let Some(&child) = index.get(&Arc::as_ptr(child)) {
+                        external[child] = external[child].saturating_sub(1);
+                        children[i].push(child);
+                    }
+                }
+            }
+        }
+
+        // everything reachable from outside is alive
+        let mut alive = vec![false; objects.len()];
+        let mut stack = (0..objects.len())
+            .filter(|&i| external[i] > 0 || locked[i])
+            .collect::<Vec<_>>();
+        while let Some(i) = stack.pop() {
+            if alive[i] {
+                continue;
+            }
+            alive[i] = true;
+            stack.extend(children[i].iter().filter(|&&child| !alive[child]));
+        }
+
+        // values are dropped after all objects are unlocked, since dropping them can free other objects
+        let mut freed = 0;
+        let mut values = Vec::new();
+        for (obj, _) in objects.iter().zip(&alive).filter(|(_, alive)| !**alive) {
+            if let Ok(mut obj) = obj.try_lock() {
+                values.extend(obj.take_values());
+                freed += 1;
+            }
+        }
+        drop(values);
+        drop(objects);
+
+        freed
+    }
+
+    /// Collects when the heap has grown enough, and checks that it's within the limits
+    pub fn check(&self, limits: &Limits) -> Result<(), Error> {
+        let len = self.objects.lock().unwrap().len();
+        let properties = self.properties.load(Ordering::Relaxed);
+        let over_limit = limits.max_objects.is_some_and(|max| len > max)
+            || limits.max_properties.is_some_and(|max| properties > max);
+        if len < self.threshold.load(Ordering::Relaxed) && !over_limit {
+            return Ok(());
+        }
+
+        self.collect();
+        let stats = self.stats();
+        self.threshold
+            .store((stats.objects * 2).max(MIN_THRESHOLD), Ordering::Relaxed);
+
+        if let Some(max_objects) = limits.max_objects {
+            if stats.objects > max_objects {
+                return Err(Error::ObjectLimit(max_objects));
+            }
+        }
+        if let Some(max_properties) = limits.max_properties {
+            if stats.properties > max_properties {
+                return Err(Error::PropertyLimit(max_properties));
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,169 @@
+//! Host I/O used by the standard library
+//!
+//! By default the interpreter uses the process' stdout and stdin, but anything that implements
+//! [`Io`] can be installed with [`Interpreter::with_io`](crate::Interpreter::with_io) or
+//! [`Interpreter::set_io`](crate::Interpreter::set_io)
+
+use std::{
+    collections::VecDeque,
+    fmt::Debug,
+    io::{self, BufRead, Write},
+    ops::{Deref, DerefMut},
+    sync::{Arc, Mutex},
+};
+
+/// Host I/O that `print` and `input` go through
+pub trait Io: Send {
+    /// Writes the string to the output as is
+    fn write(&mut self, s: &str) -> io::Result<()>;
+
+    /// Flushes any buffered output
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Reads a single line from the input
+    /// - The line terminator may or may not be included
+    fn read_line(&mut self) -> io::Result<String>;
+}
+
+/// Uses the process' stdout and stdin
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StdIo;
+
+impl Io for StdIo {
+    fn write(&mut self, s: &str) -> io::Result<()> {
+        io::stdout().lock().write_all(s.as_bytes())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stdout().flush()
+    }
+
+    fn read_line(&mut self) -> io::Result<String> {
+        let mut input = String::new();
+        io::stdin().lock().read_line(&mut input)?;
+        Ok(input)
+    }
+}
+
+/// In-memory I/O
+/// - Cloning this shares the same buffers, so a clone can be kept to inspect the output after installing it
+#[derive(Debug, Default, Clone)]
+pub struct BufferIo {
+    output: Arc<Mutex<String>>,
+    input: Arc<Mutex<VecDeque<String>>>,
+}
+
+impl BufferIo {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a buffer with lines queued up for `input`
+    pub fn with_input<I, S>(lines: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let io = Self::new();
+        for line in lines {
+            io.push_input(line);
+        }
+        io
+    }
+
+    /// Queues a line to be read by `input`
+    pub fn push_input(&self, line: impl Into<String>) {
+        self.input.lock().unwrap().push_back(line.into());
+    }
+
+    /// Gets everything that has been written so far
+    pub fn output(&self) -> String {
+        self.output.lock().unwrap().clone()
+    }
+
+    /// Takes everything that has been written so far, leaving the output empty
+    pub fn take_output(&self) -> String {
+        std::mem::take(&mut self.output.lock().unwrap())
+    }
+}
+
+impl Io for BufferIo {
+    fn write(&mut self, s: &str) -> io::Result<()> {
+        self.output.lock().unwrap().push_str(s);
+        Ok(())
+    }
+
+    fn read_line(&mut self) -> io::Result<String> {
+        // no more input is the same as EOF on stdin
+        Ok(self.input.lock().unwrap().pop_front().unwrap_or_default())
+    }
+}
+
+type OutputCallback = Box<dyn FnMut(&str) -> io::Result<()> + Send>;
+type InputCallback = Box<dyn FnMut() -> io::Result<String> + Send>;
+
+/// I/O that forwards everything to callbacks
+pub struct CallbackIo {
+    output: OutputCallback,
+    input: InputCallback,
+}
+
+impl CallbackIo {
+    pub fn new<O, I>(output: O, input: I) -> Self
+    where
+        O: FnMut(&str) -> io::Result<()> + Send + 'static,
+        I: FnMut() -> io::Result<String> + Send + 'static,
+    {
+        Self {
+            output: Box::new(output),
+            input: Box::new(input),
+        }
+    }
+}
+
+impl Debug for CallbackIo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CallbackIo").finish_non_exhaustive()
+    }
+}
+
+impl Io for CallbackIo {
+    fn write(&mut self, s: &str) -> io::Result<()> {
+        (self.output)(s)
+    }
+
+    fn read_line(&mut self) -> io::Result<String> {
+        (self.input)()
+    }
+}
+
+/// The [`Io`] that is installed on an interpreter
+pub(crate) struct IoHost(pub Box<dyn Io>);
+
+impl Default for IoHost {
+    fn default() -> Self {
+        Self(Box::new(StdIo))
+    }
+}
+
+impl Deref for IoHost {
+    type Target = dyn Io;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
+
+impl DerefMut for IoHost {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.0.as_mut()
+    }
+}
+
+impl Debug for IoHost {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("IoHost").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,54 @@
+//! Regional settings, which decide how strings are interpolated
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Decides the currency symbol that interpolates in strings, like `"Hello ${name}"` or `"Hello {name}€"`
+pub struct Locale {
+    /// Currency symbol that marks an interpolation
+    pub currency: String,
+    /// If the symbol goes after the braces instead of before them
+    pub suffix: bool,
+}
+
+/// Language tags with their currency symbol and if it goes after the braces
+const LOCALES: &[(&str, &str, bool)] = &[
+    ("en-US", "$", false),
+    ("en-GB", "£", false),
+    ("en-IE", "€", false),
+    ("en-IN", "₹", false),
+    ("ja-JP", "¥", false),
+    ("zh-CN", "¥", false),
+    ("ko-KR", "₩", false),
+    ("pt-BR", "R$", false),
+    ("pt-CV", "$", true),
+    ("de-DE", "€", true),
+    ("fr-FR", "€", true),
+    ("es-ES", "€", true),
+    ("it-IT", "€", true),
+    ("nl-NL", "€", true),
+    ("ru-RU", "₽", true),
+];
+
+impl Locale {
+    pub fn new(currency: &str, suffix: bool) -> Self {
+        Self {
+            currency: currency.to_string(),
+            suffix,
+        }
+    }
+
+    /// Gets the locale of a language tag like `en-GB`
+    /// - Only a handful of tags are known, `None` is returned for the rest
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        LOCALES
+            .iter()
+            .find(|(name, _, _)| name.eq_ignore_ascii_case(tag))
+            .map(|(_, currency, suffix)| Self::new(currency, *suffix))
+    }
+}
+
+impl Default for Locale {
+    /// `en-US`, which interpolates with `${name}`
+    fn default() -> Self {
+        Self::new("$", false)
+    }
+}
@@ -0,0 +1,45 @@
+//! Intrinsic objects that are created per interpreter
+
+use std::sync::Arc;
+
+use super::{
+    state::FunctionState,
+    stdlib::{array, function, object},
+    value::ObjectRef,
+};
+
+/// The set of prototypes objects are created from
+/// - Every interpreter creates its own realm, so monkey patching a prototype doesn't leak to other interpreters
+/// - Cloning this shares the same prototypes, use [`Interpreter::new_with_realm`](crate::Interpreter::new_with_realm) to share a realm explicitly
+#[derive(Debug, Clone)]
+pub struct Realm {
+    /// `Object.prototype`
+    pub object_prototype: ObjectRef,
+    /// `Function.prototype`
+    pub function_prototype: ObjectRef,
+    /// `Array.prototype`
+    pub array_prototype: ObjectRef,
+    /// Functions the methods of the prototypes are bound to, which are shared with the realm
+    pub(crate) methods: Arc<Vec<FunctionState>>,
+}
+
+impl Realm {
+    pub fn new() -> Self {
+        let object_prototype = object::prototype();
+        let function_prototype = function::prototype(&object_prototype);
+        let (array_prototype, methods) = array::prototype(&object_prototype, &function_prototype);
+
+        Self {
+            object_prototype,
+            function_prototype,
+            array_prototype,
+            methods: Arc::new(methods),
+        }
+    }
+}
+
+impl Default for Realm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
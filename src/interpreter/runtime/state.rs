@@ -1,36 +1,60 @@
 use std::{
-    borrow::{Borrow, Cow},
-    collections::HashMap,
-    sync::{Arc, Mutex, Weak},
-    time::Instant,
+    borrow::Cow,
+    collections::{HashMap, VecDeque},
+    fmt::Debug,
+    ops::{Deref, DerefMut},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex, Weak,
+    },
+    time::Duration,
 };
 
 use crate::{
     interpreter::{
         evaluators::{
-            conditional::When,
-            expression::{AtomPostfix, Expression},
-            statement::Statement,
-            variable::VarType,
+            cache::BodyCache, class::ClassBody, conditional::When, expression::AtomPostfix,
+            statement::Statement, variable::VarType,
         },
         static_analysis::{Analysis, HoistedVarInfo},
     },
     parsers::{types::Position, LifeTime, PosWithInfo},
     prelude::Wrapper,
     runtime,
-    runtime::{stdlib::array, value::PROTO_PROP},
+    runtime::{
+        stdlib::{
+            array,
+            signal::{Signal, SignalAccess},
+        },
+        value::PROTO_PROP,
+    },
     Interpreter,
 };
 
 use super::{
-    error::Error,
-    stdlib::function,
-    value::{Object, ObjectRef, Value},
+    budget::Budget,
+    clock::{TimerId, Timers},
+    error::{Error, Location, StackFrame, Trace},
+    files::Files,
+    heap::Heap,
+    io::IoHost,
+    locale::Locale,
+    realm::Realm,
+    store::StoreHost,
+    value::{History, Object, ObjectRef, Value, DEFAULT_HISTORY_LEN},
 };
 
 type Scope<T> = Vec<T>;
 type CallStack<T> = Vec<T>;
 
+/// Scope of the standard library and registered functions, which is shared by all files
+const GLOBAL_SCOPE: usize = 0;
+/// Scope of the file being evaluated, which is right above the global scope
+const FILE_SCOPE: usize = 1;
+
+/// Longest time `sleep` waits before checking if evaluating should stop
+const SLEEP_SLICE: Duration = Duration::from_millis(10);
+
 #[derive(Debug)]
 /// Interpreter state
 pub struct InterpreterState {
@@ -39,54 +63,152 @@ pub struct InterpreterState {
      * without keeping track of how many scopes were opened which the `return` statement
      * will have to pop on early return
      */
-    pub scope_stacks: Arc<Mutex<CallStack<Scope<ScopeState>>>>,
+    pub scope_stacks: Arc<Mutex<CallStack<CallFrame>>>,
     // function status. extra information that objects don't have
     pub funcs: Arc<Mutex<Functions>>,
+    // class status, same as functions
+    classes: Mutex<Vec<ClassState>>,
     // hoisted variable info
     hoisted_vars: Arc<Mutex<Vec<HoistedVarInfo>>>,
     // TODO: atomic type
     pub exec_reverse: Arc<Mutex<bool>>,
+    // where `print` and `input` goes
+    pub io: Arc<Mutex<IoHost>>,
+    // prototypes, this is per interpreter unless shared explicitly
+    pub realm: Realm,
+    // check the code for problems before evaluating
+    pub parse_diagnostics: AtomicBool,
+    // where the last runtime error happened
+    error_trace: Mutex<Option<Trace>>,
+    // names looked up by the parses in progress, the innermost parse is last
+    parse_lookups: Mutex<Vec<Vec<Lookup>>>,
+    // how many past values are kept for `previous`
+    pub history_len: AtomicUsize,
+    // variables that `next` can be read from, which are the ones whose assignment is being waited for
+    resolved_next: Mutex<Vec<Vec<String>>>,
+    // statements suspended by `next`, which outlive the scope they were suspended in
+    waits: Mutex<Vec<Wait>>,
+    // primitives and keywords that were deleted
+    deleted: Mutex<Deleted>,
+    // the file being evaluated and what was exported between files
+    pub files: Mutex<Files>,
+    // limits on how much the code can do
+    pub budget: Budget,
+    // objects created by the code
+    pub heap: Heap,
+    // async function calls that take turns with the main program, finished ones are `None` during a turn
+    tasks: Mutex<Vec<Option<Task>>>,
+    // decides the currency symbol strings are interpolated with
+    pub locale: Mutex<Locale>,
+    // assignments that haven't triggered the `when` watchers yet
+    when_queue: Mutex<WhenQueue>,
+    // where `const const const` values are kept
+    pub store: Mutex<StoreHost>,
+    // writes to constants are errors instead of being ignored
+    pub strict: AtomicBool,
+    // the clock and the variables that expire with it
+    pub timers: Mutex<Timers>,
 }
 
 impl Default for InterpreterState {
     fn default() -> Self {
-        let scope_stacks = Arc::new(Mutex::new(vec![vec![ScopeState {
-            vars: VariableState::default(),
-            whens: Vec::new(),
-        }]]));
+        Self::new(Realm::default())
+    }
+}
+
+impl InterpreterState {
+    pub fn new(realm: Realm) -> Self {
+        let scope_stacks = Arc::new(Mutex::new(vec![CallFrame::default()]));
 
         Self {
             scope_stacks,
             funcs: Arc::new(Mutex::new(Functions::default())),
+            classes: Mutex::new(Vec::new()),
             hoisted_vars: Arc::new(Mutex::new(Vec::new())),
             exec_reverse: Arc::new(Mutex::new(false)),
+            io: Arc::new(Mutex::new(IoHost::default())),
+            realm,
+            parse_diagnostics: AtomicBool::new(false),
+            error_trace: Mutex::new(None),
+            parse_lookups: Mutex::new(Vec::new()),
+            history_len: AtomicUsize::new(DEFAULT_HISTORY_LEN),
+            resolved_next: Mutex::new(Vec::new()),
+            waits: Mutex::new(Vec::new()),
+            deleted: Mutex::new(Deleted::default()),
+            files: Mutex::new(Files::default()),
+            budget: Budget::default(),
+            heap: Heap::default(),
+            tasks: Mutex::new(Vec::new()),
+            locale: Mutex::new(Locale::default()),
+            when_queue: Mutex::new(WhenQueue::default()),
+            store: Mutex::new(StoreHost::default()),
+            strict: AtomicBool::new(false),
+            timers: Mutex::new(Timers::default()),
         }
     }
-}
 
-impl InterpreterState {
+    /// Counts a statement that is about to be executed, checking the limits
+    pub fn step(&self) -> Result<(), Error> {
+        self.budget.step()?;
+        let limits = *self.budget.limits.lock().unwrap();
+        self.heap.check(&limits)
+    }
+
     // TODO: test this
     pub fn toggle_reverse(&self) {
         let mut reverse = self.exec_reverse.lock().unwrap();
         *reverse = !*reverse;
     }
 
+    /// Runs the parser while keeping track of the names it looks up
+    pub fn record_lookups<T>(&self, parse: impl FnOnce() -> T) -> (T, Vec<Lookup>) {
+        self.parse_lookups.lock().unwrap().push(Vec::new());
+        let res = parse();
+        let lookups = self.parse_lookups.lock().unwrap().pop().unwrap();
+        (res, lookups)
+    }
+
+    fn record_lookup(&self, lookup: impl FnOnce() -> Lookup) {
+        let mut parse_lookups = self.parse_lookups.lock().unwrap();
+        let Some(lookups) = parse_lookups.last_mut() else {
+            return;
+        };
+        let lookup = lookup();
+        if !lookups.contains(&lookup) {
+            lookups.push(lookup);
+        }
+    }
+
+    /// Checks if the names still resolve to what they did when they were recorded
+    pub fn lookups_valid(&self, lookups: &[Lookup], args: PosWithInfo) -> bool {
+        lookups.iter().all(|lookup| match lookup {
+            Lookup::Var { name, found } => self.find_var_map(name, |_| ()).is_some() == *found,
+            Lookup::Func { name, arg_count } => {
+                self.find_func(name, args).map(|func| func.arg_count) == *arg_count
+            }
+            Lookup::Identifier { name, resolved } => {
+                self.find_identifier(name, args)
+                    .as_ref()
+                    .map(Resolved::from)
+                    == *resolved
+            }
+            Lookup::Deleted { count } => self.deleted.lock().unwrap().count() == *count,
+        })
+    }
+
     /// Gets function info
     pub fn get_func_info(&self, name: &str, args: PosWithInfo) -> Option<FunctionState> {
+        let func = self.find_func(name, args);
+        self.record_lookup(|| Lookup::Func {
+            name: name.to_string(),
+            arg_count: func.as_ref().map(|func| func.arg_count),
+        });
+        func
+    }
+
+    fn find_func(&self, name: &str, args: PosWithInfo) -> Option<FunctionState> {
         self.clean_up_funcs();
-        let find_func = |value: &Value| {
-            let Value::Object(Some(value)) = value else {
-                return None;
-            };
-            // find the function
-            self.funcs
-                .lock()
-                .unwrap()
-                .0
-                .iter()
-                .find(|func| Weak::ptr_eq(&func.obj, &Arc::downgrade(value)))
-                .cloned()
-        };
+        let find_func = |value: &Value| self.func_of(value);
         // TODO: debug when statement and find out why this is locking up
         let res = self
             .scope_stacks
@@ -96,12 +218,7 @@ impl InterpreterState {
             .rev()
             .find_map(|scopes| {
                 scopes.iter_mut().rev().find_map(|scope| {
-                    let vars = &mut scope.vars;
-                    vars.validate_lifetime();
-
-                    let Some(vars) = vars.get_var(name) else {
-                        return None;
-                    };
+                    let vars = scope.vars.get_var(name)?;
                     find_func(vars.get_value())
                 })
             });
@@ -109,6 +226,13 @@ impl InterpreterState {
             return res;
         }
 
+        // method of an object
+        if name.contains('.') {
+            if let Some(func) = self.find_path(name).and_then(|value| find_func(&value)) {
+                return Some(func);
+            }
+        }
+
         // check if it's hoisted
         let Ok(vars) = self.hoisted_vars.try_lock() else {
             return None;
@@ -125,6 +249,35 @@ impl InterpreterState {
         found
     }
 
+    /// Finds the function bound to the object
+    fn func_of(&self, value: &Value) -> Option<FunctionState> {
+        let Value::Object(Some(value)) = value else {
+            return None;
+        };
+        let is_bound = |func: &&FunctionState| Weak::ptr_eq(&func.obj, &Arc::downgrade(value));
+        if let Some(func) = self.funcs.lock().unwrap().0.iter().find(is_bound) {
+            return Some(func.clone());
+        }
+
+        // methods of the prototypes
+        self.realm.methods.iter().find(is_bound).cloned()
+    }
+
+    /// Calls a function value, for methods the runtime calls by itself like `valueOf`
+    /// # Returns
+    /// - `None` if the value isn't a function
+    pub fn call_value(
+        &self,
+        eval_args: PosWithInfo,
+        frame: StackFrame,
+        func: &Value,
+        this: Value,
+        args: Vec<Wrapper<Cow<Value>>>,
+    ) -> Option<Result<Value, Error>> {
+        let func = self.func_of(func)?;
+        Some(func.eval(eval_args, frame, Some(this), args))
+    }
+
     /// Clean up functions that can't be called anymore
     fn clean_up_funcs(&self) {
         self.funcs
@@ -158,14 +311,14 @@ impl InterpreterState {
 
         scopes.push(ScopeState {
             vars: VariableState(new_vars),
-            whens: Vec::new(),
+            ..Default::default()
         });
     }
 
     pub fn pop_scope(&self, line: usize) {
         let mut scope_stacks = self.scope_stacks.lock().unwrap();
         let scopes = scope_stacks.last_mut().unwrap();
-        if scopes.len() == 1 {
+        if scopes.len() <= scopes.base_len() {
             return;
         }
 
@@ -179,19 +332,199 @@ impl InterpreterState {
         }
     }
 
+    /// Number of scopes of the current function call
+    pub fn scope_depth(&self) -> usize {
+        self.scope_stacks.lock().unwrap().last().unwrap().len()
+    }
+
+    /// Pops the scopes pushed after the depth, for a body that is left before its scopes end
+    pub fn pop_scopes_to(&self, depth: usize, line: usize) {
+        while self.scope_depth() > depth {
+            self.pop_scope(line);
+        }
+    }
+
     pub fn invoke_func(
         &self,
         eval_args: PosWithInfo,
         name: &str,
+        call_site: Location,
         args: Vec<Wrapper<Cow<Value>>>,
     ) -> Result<Value, Error> {
         if let Some(func) = self.get_func_info(name, eval_args) {
-            return func.eval(eval_args, args);
+            let frame = StackFrame {
+                name: name.to_string(),
+                call_site,
+            };
+            // method call
+            let path = match name.rsplit_once('.') {
+                Some((path, _)) if self.find_var_map(name, |_| ()).is_none() => Some(path),
+                _ => None,
+            };
+            let this = path.and_then(|path| self.find_path(path));
+
+            // a method that changes the object is a write to the variable holding it
+            let written = match (&func.variant, path) {
+                (FunctionVariant::MutatingMethod(_), Some(path)) => path.split('.').next(),
+                _ => None,
+            };
+            let Some(var_name) = written else {
+                return func.eval(eval_args, frame, this, args);
+            };
+            if !self.check_write(var_name, true)? {
+                return Ok(Value::Undefined);
+            }
+            let value = func.eval(eval_args, frame, this, args)?;
+            let var_value = self.get_var_value(var_name, 0).unwrap_or(Value::Undefined);
+            self.update_when(eval_args, var_name, var_value)?;
+            return Ok(value);
         }
 
         Err(Error::FunctionNotFound(name.to_string()))
     }
 
+    /// Starts evaluating a new file, which gets its own scope above the global scope
+    /// - Anything declared by the previous file is gone, except what it exported
+    pub fn enter_file(&self, name: Option<&str>) {
+        let mut scope_stacks = self.scope_stacks.lock().unwrap();
+        scope_stacks.truncate(1);
+        let frame = scope_stacks.first_mut().unwrap();
+        frame.truncate(FILE_SCOPE);
+        frame.push(ScopeState::default());
+        drop(scope_stacks);
+
+        self.files.lock().unwrap().enter(name);
+    }
+
+    /// Runs `f` in a new call frame
+    pub fn call_frame<T>(&self, frame: StackFrame, f: impl FnOnce() -> T) -> T {
+        self.scope_stacks
+            .lock()
+            .unwrap()
+            .push(CallFrame::new(frame));
+        let res = f();
+        self.scope_stacks.lock().unwrap().pop();
+        res
+    }
+
+    /// Starts an async function call, which takes its first turn after the current statement
+    /// - `declare` declares the arguments in the scope of the call
+    fn spawn_task(
+        &self,
+        frame: StackFrame,
+        declare: impl FnOnce(),
+        body: &Arc<String>,
+        cache: &Arc<BodyCache>,
+        code_with_pos: PosWithInfo,
+    ) {
+        self.scope_stacks
+            .lock()
+            .unwrap()
+            .push(CallFrame::new(frame));
+        declare();
+
+        // opening the block isn't a turn
+        let block_start = cache
+            .statement(code_with_pos)
+            .ok()
+            .filter(|(_, statement)| matches!(**statement, Statement::ScopeStart(_)));
+        let (start, scope_count) = match block_start {
+            Some((code_after, _)) => (code_after, Some(1)),
+            None => (code_with_pos, None),
+        };
+        let frame = self.scope_stacks.lock().unwrap().pop().unwrap();
+
+        self.tasks.lock().unwrap().push(Some(Task {
+            frame,
+            body: Arc::clone(body),
+            cache: Arc::clone(cache),
+            next: (start.index, start.line, start.column),
+            scope_count,
+        }));
+    }
+
+    /// Runs a statement of each async function call that hasn't finished, in the order they were called
+    /// - Calls started during the turn also run in it
+    pub fn run_turn(&self, args: PosWithInfo) -> Result<(), Error> {
+        let mut res = Ok(());
+        let mut i = 0;
+        loop {
+            let task = match self.tasks.lock().unwrap().get_mut(i) {
+                Some(task) => task.take(),
+                None => break,
+            };
+            let Some(mut task) = task else {
+                i += 1;
+                continue;
+            };
+
+            match task.step(self, args) {
+                Ok(false) => self.tasks.lock().unwrap()[i] = Some(task),
+                Ok(true) => (),
+                Err(err) => {
+                    res = Err(err);
+                    break;
+                }
+            }
+            i += 1;
+        }
+
+        self.tasks.lock().unwrap().retain(Option::is_some);
+        res
+    }
+
+    /// Takes turns until every async function call has finished
+    pub fn run_tasks(&self, args: PosWithInfo) -> Result<(), Error> {
+        while !self.tasks.lock().unwrap().is_empty() {
+            self.run_turn(args)?;
+        }
+        Ok(())
+    }
+
+    /// Drops the async function calls that haven't finished, so they don't continue in the next evaluation
+    pub fn clear_tasks(&self) {
+        self.tasks.lock().unwrap().clear();
+    }
+
+    pub fn add_class(&self, class: ClassState) {
+        let mut classes = self.classes.lock().unwrap();
+        classes.retain(|class| class.obj.upgrade().is_some());
+        classes.push(class);
+    }
+
+    /// Gets the class that the object is
+    pub fn get_class(&self, obj: &ObjectRef) -> Option<ClassState> {
+        self.classes
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|class| Weak::ptr_eq(&class.obj, &Arc::downgrade(obj)))
+            .cloned()
+    }
+
+    /// Records where an error happened along with the current call stack
+    /// - Only the first call is recorded, which is the innermost statement as the error propagates outwards
+    pub fn trace_error(&self, location: Location) {
+        let mut error_trace = self.error_trace.lock().unwrap();
+        if error_trace.is_some() {
+            return;
+        }
+
+        let stack = self
+            .scope_stacks
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|frame| frame.call.clone())
+            .collect();
+        *error_trace = Some(Trace { location, stack });
+    }
+
+    /// Takes the recorded error trace, if any
+    pub fn take_error_trace(&self) -> Option<Trace> {
+        self.error_trace.lock().unwrap().take()
+    }
+
     pub fn add_var(
         &self,
         name: &str,
@@ -200,6 +533,11 @@ impl InterpreterState {
         type_: VarType,
         life_time: Option<LifeTime>,
     ) {
+        let mut var = Variable::new(value, line, type_);
+        if let Some(LifeTime::Seconds(seconds)) = life_time {
+            var.timer = self.timers.lock().unwrap().schedule(name, seconds);
+        }
+
         self.scope_stacks
             .lock()
             .unwrap()
@@ -208,7 +546,7 @@ impl InterpreterState {
             .last_mut()
             .unwrap()
             .vars
-            .declare_var(name, value, line, type_, life_time);
+            .declare(name, var);
     }
 
     // identical to add_var, but for runtime
@@ -221,31 +559,244 @@ impl InterpreterState {
         life_time: Option<LifeTime>,
         args: PosWithInfo,
     ) -> Result<(), runtime::Error> {
+        self.add_var(name, value.to_owned(), line, type_, life_time);
+        self.update_when(args, name, value)
+    }
+
+    /// Expires the variables whose lifetime in seconds is up, restoring the variables they replaced
+    /// - The `when` watchers are triggered with what the name refers to after that, which is `undefined` if nothing was replaced
+    pub fn expire_timers(&self, args: PosWithInfo) -> Result<(), Error> {
+        let expired = self.timers.lock().unwrap().expired();
+        if expired.is_empty() {
+            return Ok(());
+        }
+
+        // every variable is expired before any watcher runs, so they don't see the ones that are left over
+        let mut changed = Vec::new();
+        for timer in expired {
+            let visible = self
+                .scope_stacks
+                .lock()
+                .unwrap()
+                .iter_mut()
+                .flat_map(|frame| frame.iter_mut())
+                .find_map(|scope| scope.vars.expire(&timer.name, timer.id));
+            if visible == Some(true) && !changed.contains(&timer.name) {
+                changed.push(timer.name);
+            }
+        }
+
+        for name in changed {
+            let value = self
+                .find_var_map(&name, |var| var.get_value().clone())
+                .unwrap_or(Value::Undefined);
+            self.update_when(args, &name, value)?;
+        }
+        Ok(())
+    }
+
+    /// Waits for the duration on the clock, expiring the variables whose lifetime is up while waiting
+    pub fn sleep(&self, duration: Duration, args: PosWithInfo) -> Result<(), Error> {
+        let end = self.timers.lock().unwrap().now() + duration;
+        loop {
+            self.budget.poll()?;
+            {
+                let mut timers = self.timers.lock().unwrap();
+                let now = timers.now();
+                if now >= end {
+                    return Ok(());
+                }
+                let until = timers.next_deadline().map_or(end, |next| next.min(end));
+                // short slices, so the deadline and cancelling stop the sleep
+                timers.sleep(until.saturating_sub(now).min(SLEEP_SLICE));
+            }
+            self.expire_timers(args)?;
+        }
+    }
+
+    pub fn get_var(&self, name: &str) -> Option<Variable> {
+        let var = self.find_var(name);
+        self.record_lookup(|| Lookup::Var {
+            name: name.to_string(),
+            found: var.is_some(),
+        });
+        var
+    }
+
+    /// Gets the value at a property path such as `obj.prop.method`
+    fn find_path(&self, path: &str) -> Option<Value> {
+        let mut names = path.split('.');
+        let mut value = self.get_var_value(names.next()?, 0)?;
+        for name in names {
+            let Value::Object(Some(obj)) = value else {
+                return None;
+            };
+            value = obj.lock().unwrap().get_property(name)?;
+        }
+        Some(value)
+    }
+
+    /// Gets the value of a variable without copying the rest of it
+    /// - `steps` is how many assignments ago, where 0 is the current value
+    pub fn get_var_value(&self, name: &str, steps: usize) -> Option<Value> {
+        self.find_var_map(name, |var| var.get_value_at(steps))
+    }
+
+    /// Gets the value for `next`, which is only known while the assignment it waits for is happening
+    /// # Errors
+    /// - [`Error::Suspended`] if the variable hasn't been assigned yet
+    pub fn get_next_var_value(&self, name: &str) -> Result<Value, Error> {
+        let resolved = self
+            .resolved_next
+            .lock()
+            .unwrap()
+            .last()
+            .is_some_and(|names| names.iter().any(|resolved| resolved == name));
+        if !resolved {
+            return Err(Error::Suspended(name.to_string()));
+        }
+
+        Ok(self.get_var_value(name, 0).unwrap_or(Value::Undefined))
+    }
+
+    /// Removes the variable from the innermost scope that has it
+    pub fn delete_var(&self, name: &str) -> bool {
         self.scope_stacks
             .lock()
             .unwrap()
-            .last_mut()
+            .iter_mut()
+            .rev()
+            .flat_map(|frame| frame.iter_mut().rev())
+            .any(|scope| scope.vars.0.remove(name).is_some())
+    }
+
+    /// Deletes the primitive, so it can't be used anymore
+    pub fn delete_value(&self, value: Value) {
+        self.deleted.lock().unwrap().values.push(value);
+    }
+
+    /// Deletes the keyword, so statements using it can't be used anymore
+    pub fn delete_keyword(&self, keyword: &str) {
+        self.deleted
+            .lock()
             .unwrap()
-            .last_mut()
+            .keywords
+            .push(keyword.to_string());
+    }
+
+    pub fn is_value_deleted(&self, value: &Value) -> bool {
+        let deleted = self.deleted.lock().unwrap();
+        self.record_lookup(|| Lookup::Deleted {
+            count: deleted.count(),
+        });
+        deleted
+            .values
+            .iter()
+            .any(|deleted| deleted.strict_eq(value))
+    }
+
+    pub fn is_keyword_deleted(&self, keyword: &str) -> bool {
+        let deleted = self.deleted.lock().unwrap();
+        self.record_lookup(|| Lookup::Deleted {
+            count: deleted.count(),
+        });
+        deleted.keywords.iter().any(|deleted| deleted == keyword)
+    }
+
+    /// Suspends the statement until the variable is assigned, which resumes it in the current scope
+    pub fn wait_for_next(&self, name: &str, statement: Statement) {
+        let scope = self
+            .scope_stacks
+            .lock()
             .unwrap()
-            .vars
-            .declare_var(name, value.to_owned(), line, type_, life_time);
+            .last()
+            .unwrap()
+            .last()
+            .unwrap()
+            .id;
+        self.waits.lock().unwrap().push(Wait {
+            name: name.to_string(),
+            resolved: Vec::new(),
+            scope,
+            statement,
+        });
+    }
 
-        self.update_when(args, name, value)
+    fn find_var(&self, name: &str) -> Option<Variable> {
+        self.find_var_map(name, Variable::clone)
     }
 
-    pub fn get_var(&self, name: &str) -> Option<Variable> {
-        self.scope_stacks
+    /// Finds the variable, where variables of the scopes shadow `const const const` values of the store
+    fn find_var_map<T>(&self, name: &str, f: impl FnOnce(&Variable) -> T) -> Option<T> {
+        {
+            let mut scope_stacks = self.scope_stacks.lock().unwrap();
+            let var = scope_stacks.iter_mut().rev().find_map(|scope_stack| {
+                scope_stack
+                    .iter_mut()
+                    .rev()
+                    .find_map(|scope| scope.vars.get_var(name))
+            });
+            if let Some(var) = var {
+                return Some(f(var));
+            }
+        }
+
+        let value = self.get_const_const_const(name)?;
+        Some(f(&Variable::new(value, 0, VarType::ConstConstConst)))
+    }
+
+    /// Gets the `const const const` value from the store
+    /// - A store that fails to be read is the same as the value not being there
+    fn get_const_const_const(&self, name: &str) -> Option<Value> {
+        self.store.lock().unwrap().get(name).ok().flatten()
+    }
+
+    /// Declares a `const const const` value in the store, which every interpreter sharing the store can see
+    pub fn declare_const_const_const(
+        &self,
+        name: &str,
+        value: Value,
+        args: PosWithInfo,
+    ) -> Result<(), Error> {
+        if matches!(value, Value::Symbol(_) | Value::Object(Some(_))) {
+            return Err(Error::Type(format!(
+                "const const const {name} can only be a primitive"
+            )));
+        }
+
+        let declared = self
+            .store
             .lock()
             .unwrap()
-            .iter_mut()
-            .find_map(|scope_stack| {
-                scope_stack.iter_mut().rev().find_map(|scope| {
-                    let var = &mut scope.vars;
-                    var.validate_lifetime();
-                    var.get_var(name).cloned()
-                })
-            })
+            .declare(name, &value)
+            .map_err(|err| Error::RuntimeException(err.to_string()))?;
+        if !declared {
+            return Err(Error::Redeclared(name.to_string()));
+        }
+
+        self.update_when(args, name, value)
+    }
+
+    /// Checks if the variable can be assigned to, or have its properties set if `is_property`
+    /// - This only fails in strict mode, otherwise writes to constants are ignored
+    /// # Returns
+    /// - `false` if the write is ignored
+    pub fn check_write(&self, name: &str, is_property: bool) -> Result<bool, Error> {
+        let writable = self.find_var_map(name, |var| {
+            if is_property {
+                var.is_mutable()
+            } else {
+                var.is_reassignable()
+            }
+        });
+
+        match writable {
+            Some(false) if !self.strict.load(Ordering::Relaxed) => Ok(false),
+            Some(false) if is_property => Err(Error::ConstMutation(name.to_string())),
+            Some(false) => Err(Error::ConstAssignment(name.to_string())),
+            // assigning to an undeclared variable declares it
+            Some(true) | None => Ok(true),
+        }
     }
 
     pub fn set_var(
@@ -256,13 +807,22 @@ impl InterpreterState {
         value: Value,
         line: usize,
     ) -> Result<(), Error> {
+        // the scopes aren't locked while setting a property, since its key can use variables
+        if !postfix.is_empty() {
+            if let Some(var) = self.get_var(name) {
+                var.set_property(args, value.clone(), postfix)?;
+                self.update_when(args, name, value)?;
+                return Ok(());
+            }
+        }
+
         let mut scope_stacks = self.scope_stacks.lock().unwrap();
         let mut var_found = false;
-        'outer_vars: for scope in scope_stacks.iter_mut() {
+        'outer_vars: for scope in scope_stacks.iter_mut().rev() {
             let scopes_iter = scope.iter_mut().rev();
 
             for scope in scopes_iter {
-                if scope.vars.set_var(name, args, postfix, &value)? {
+                if scope.vars.set_var(name, args, &value) {
                     var_found = true;
                     break 'outer_vars;
                 }
@@ -275,15 +835,22 @@ impl InterpreterState {
             self.update_when(args, name, value)?;
             return Ok(());
         }
+        drop(scope_stacks);
 
-        // declare global
+        // like other constants, assigning to a `const const const` does nothing
+        if self.get_const_const_const(name).is_some() {
+            return Ok(());
+        }
+
+        let mut scope_stacks = self.scope_stacks.lock().unwrap();
+
+        // declare global, which is only global to the file
         scope_stacks
             .first_mut()
             .unwrap()
-            .first_mut()
-            .unwrap()
+            .file_scope_mut()
             .vars
-            .declare_var(name, value.clone(), line, VarType::VarVar, None);
+            .declare_var(name, value.clone(), line, VarType::VarVar);
 
         drop(scope_stacks);
         self.update_when(args, name, value)?;
@@ -299,16 +866,11 @@ impl InterpreterState {
         let mut properties = HashMap::new();
         properties.insert(
             PROTO_PROP.to_string(),
-            Arc::clone(&function::PROTOTYPE).into(),
+            Arc::clone(&self.realm.function_prototype).into(),
         );
-        let obj = Object::new(properties);
-        let obj = Arc::new(Mutex::new(obj));
+        let obj = self.heap.alloc(Object::new(&self.realm, properties));
 
-        let state = FunctionState {
-            arg_count,
-            variant: func,
-            obj: Arc::downgrade(&obj),
-        };
+        let state = FunctionState::new(&obj, func, arg_count);
         self.funcs.lock().unwrap().0.push(state);
         obj
     }
@@ -325,17 +887,29 @@ impl InterpreterState {
             0
         };
         let obj = self.add_func(func, arg_count);
-        self.add_var(name, obj.into(), line, VarType::VarVar, None);
+        // these are usable from every file
+        self.scope_stacks.lock().unwrap().first_mut().unwrap()[GLOBAL_SCOPE]
+            .vars
+            .declare_var(name, obj.into(), line, VarType::VarVar);
     }
 
     /// Tries to get the latest defined variable or function with the given name
     pub fn get_identifier(&self, name: &str, args: PosWithInfo) -> Option<DefineType> {
+        let identifier = self.find_identifier(name, args);
+        self.record_lookup(|| Lookup::Identifier {
+            name: name.to_string(),
+            resolved: identifier.as_ref().map(Resolved::from),
+        });
+        identifier
+    }
+
+    fn find_identifier(&self, name: &str, args: PosWithInfo) -> Option<DefineType> {
         // check functions first
-        let func = self.get_func_info(name, args);
-        let var = self.get_var(name);
+        let func = self.find_func(name, args);
+        let var = self.find_var(name);
 
         let Some(func) = func else {
-            return var.map(DefineType::Var);
+            return var.map(|_| DefineType::Var);
         };
 
         let Some(var) = var else {
@@ -355,18 +929,21 @@ impl InterpreterState {
                 ..
             } => {
                 if var.line > defined_line {
-                    DefineType::Var(var)
+                    DefineType::Var
                 } else {
                     DefineType::Func(func)
                 }
             }
-            FunctionVariant::Native(_) => DefineType::Var(var),
+            FunctionVariant::Native(_)
+            | FunctionVariant::Method(_)
+            | FunctionVariant::MutatingMethod(_)
+            | FunctionVariant::Signal { .. } => DefineType::Var,
         };
 
         Some(ret)
     }
 
-    pub fn push_when(&self, when: &When) {
+    pub fn push_when(&self, when: When) {
         self.scope_stacks
             .lock()
             .unwrap()
@@ -375,14 +952,59 @@ impl InterpreterState {
             .last_mut()
             .unwrap()
             .whens
-            .push(when.clone().into());
+            .push(when.into());
     }
 
+    /// Triggers the `when` watchers of the variable, and resumes the statements waiting for it with `next`
+    /// - An assignment made while triggering is queued, and triggered by the first assignment once the current one is done
+    /// - This keeps a `when` that triggers itself from growing the stack, so it can be used as a loop
     fn update_when(
         &self,
         eval_args: PosWithInfo,
         var_name: &str,
         value: Value,
+    ) -> Result<(), Error> {
+        let depth = self.budget.when_depth();
+        {
+            let mut queue = self.when_queue.lock().unwrap();
+            queue.pending.push_back(Assignment {
+                name: var_name.to_string(),
+                value,
+                when_depth: depth,
+            });
+            if queue.draining {
+                return Ok(());
+            }
+            queue.draining = true;
+        }
+
+        let res = self.drain_whens(eval_args);
+
+        let mut queue = self.when_queue.lock().unwrap();
+        queue.draining = false;
+        // the rest isn't triggered after an error
+        queue.pending.clear();
+        self.budget.set_when_depth(depth);
+
+        res
+    }
+
+    fn drain_whens(&self, eval_args: PosWithInfo) -> Result<(), Error> {
+        loop {
+            let Some(assignment) = self.when_queue.lock().unwrap().pending.pop_front() else {
+                return Ok(());
+            };
+            // bodies triggered by the assignment count as inside the body it was made in
+            self.budget.set_when_depth(assignment.when_depth);
+            self.trigger_when(eval_args, &assignment.name, assignment.value)?;
+        }
+    }
+
+    fn trigger_when(
+        &self,
+        eval_args: PosWithInfo,
+        var_name: &str,
+        value: Value,
     ) -> Result<(), Error> {
         // TODO: only update if when expr has a variable in it
         let when_stack = self
@@ -398,82 +1020,329 @@ impl InterpreterState {
             when.eval_body(eval_args, var_name, value.to_owned())?;
         }
 
+        // statements waiting for this assignment with `next`
+        let waits = {
+            let mut waits = self.waits.lock().unwrap();
+            let (resumed, rest) = std::mem::take(&mut *waits)
+                .into_iter()
+                .partition(|wait: &Wait| wait.name == var_name);
+            *waits = rest;
+            resumed
+        };
+
+        for wait in waits {
+            self.resume_wait(eval_args, wait)?;
+        }
+
         Ok(())
     }
+
+    /// Triggers the `when` watchers that use a getter or setter of the signal, with its new value
+    fn update_signal(
+        &self,
+        eval_args: PosWithInfo,
+        signal: &Arc<Signal>,
+        value: Value,
+    ) -> Result<(), Error> {
+        let mut names = self
+            .scope_stacks
+            .lock()
+            .unwrap()
+            .iter()
+            .flatten()
+            .flat_map(|scope| scope.whens.iter())
+            .flat_map(|when| when.identifiers())
+            .cloned()
+            .collect::<Vec<_>>();
+        names.sort();
+        names.dedup();
+
+        for name in names {
+            let is_signal = self.find_func(&name, eval_args).is_some_and(|func| {
+                matches!(&func.variant, FunctionVariant::Signal { signal: other, .. } if Arc::ptr_eq(signal, other))
+            });
+            if is_signal {
+                self.update_when(eval_args, &name, value.clone())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn resume_wait(&self, eval_args: PosWithInfo, wait: Wait) -> Result<(), Error> {
+        let Wait {
+            name,
+            mut resolved,
+            scope,
+            statement,
+        } = wait;
+        resolved.push(name);
+
+        self.resolved_next.lock().unwrap().push(resolved.clone());
+        let res = self.in_scope(scope, || statement.eval(eval_args));
+        self.resolved_next.lock().unwrap().pop();
+
+        match res {
+            // waiting for another variable too
+            Err(Error::Suspended(name)) => {
+                self.waits.lock().unwrap().push(Wait {
+                    name,
+                    resolved,
+                    scope,
+                    statement,
+                });
+                Ok(())
+            }
+            res => res.map(|_| ()),
+        }
+    }
+
+    /// Runs `f` with the scope of the id as the current scope, by setting aside the scopes and call frames after it
+    /// - If the scope has ended, `f` runs in a new call frame instead
+    fn in_scope<T>(&self, id: usize, f: impl FnOnce() -> T) -> T {
+        let mut scope_stacks = self.scope_stacks.lock().unwrap();
+        let found = scope_stacks.iter().enumerate().find_map(|(i, frame)| {
+            frame
+                .iter()
+                .position(|scope| scope.id == id)
+                .map(|scope| (i, scope))
+        });
+        let Some((frame, scope)) = found else {
+            scope_stacks.push(CallFrame::default());
+            drop(scope_stacks);
+            let res = f();
+            self.scope_stacks.lock().unwrap().pop();
+            return res;
+        };
+
+        let frames = scope_stacks.split_off(frame + 1);
+        let scopes = scope_stacks[frame].split_off(scope + 1);
+        drop(scope_stacks);
+
+        let res = f();
+
+        let mut scope_stacks = self.scope_stacks.lock().unwrap();
+        scope_stacks[frame].extend(scopes);
+        scope_stacks.extend(frames);
+        res
+    }
+}
+
+#[derive(Debug, Default)]
+/// Assignments waiting to trigger the `when` watchers
+struct WhenQueue {
+    /// If the assignments are being triggered, which is done by the assignment that started it
+    draining: bool,
+    pending: VecDeque<Assignment>,
+}
+
+#[derive(Debug)]
+struct Assignment {
+    name: String,
+    value: Value,
+    /// Number of `when` bodies the assignment was made inside of
+    when_depth: usize,
 }
 
 #[derive(Debug)]
 pub enum DefineType {
-    Var(Variable),
+    Var,
     Func(FunctionState),
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A name that was looked up while parsing, and what it resolved to
+/// - What gets parsed depends on which names are defined, so this is used to tell if a cached parse can be reused
+pub enum Lookup {
+    Var {
+        name: String,
+        found: bool,
+    },
+    Func {
+        name: String,
+        arg_count: Option<Option<usize>>,
+    },
+    Identifier {
+        name: String,
+        resolved: Option<Resolved>,
+    },
+    /// Anything that was deleted can change what gets parsed
+    Deleted {
+        count: usize,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// What an identifier resolved to, which is all the parser cares about
+pub enum Resolved {
+    Var,
+    Func { arg_count: Option<usize> },
+}
+
+impl From<&DefineType> for Resolved {
+    fn from(value: &DefineType) -> Self {
+        match value {
+            DefineType::Var => Self::Var,
+            DefineType::Func(func) => Self::Func {
+                arg_count: func.arg_count,
+            },
+        }
+    }
+}
+
+#[derive(Debug)]
+/// Scopes of a single function call
+pub struct CallFrame {
+    /// The function call this is for, which is `None` at the top level
+    pub call: Option<StackFrame>,
+    scopes: Scope<ScopeState>,
+}
+
+impl CallFrame {
+    fn new(call: StackFrame) -> Self {
+        Self {
+            call: Some(call),
+            scopes: vec![ScopeState::default()],
+        }
+    }
+
+    /// Number of scopes that can't be popped by `}`
+    fn base_len(&self) -> usize {
+        match self.call {
+            Some(_) => 1,
+            None => FILE_SCOPE + 1,
+        }
+    }
+
+    /// Gets the outermost scope of the file, or of the function call
+    fn file_scope_mut(&mut self) -> &mut ScopeState {
+        let index = FILE_SCOPE.min(self.scopes.len() - 1);
+        &mut self.scopes[index]
+    }
+}
+
+impl Default for CallFrame {
+    fn default() -> Self {
+        Self {
+            call: None,
+            scopes: vec![ScopeState::default()],
+        }
+    }
+}
+
+impl Deref for CallFrame {
+    type Target = Scope<ScopeState>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.scopes
+    }
+}
+
+impl DerefMut for CallFrame {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.scopes
+    }
+}
+
+impl<'a> IntoIterator for &'a CallFrame {
+    type Item = &'a ScopeState;
+    type IntoIter = std::slice::Iter<'a, ScopeState>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.scopes.iter()
+    }
+}
+
+#[derive(Debug)]
 pub struct ScopeState {
+    // tells scopes apart, since they move between the stacks
+    id: usize,
     vars: VariableState,
     // all of the `when` that are active
     whens: Vec<Arc<When>>,
 }
 
+impl Default for ScopeState {
+    fn default() -> Self {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+        Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            vars: VariableState::default(),
+            whens: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+/// Primitives and keywords that were deleted
+struct Deleted {
+    values: Vec<Value>,
+    keywords: Vec<String>,
+}
+
+impl Deleted {
+    fn count(&self) -> usize {
+        self.values.len() + self.keywords.len()
+    }
+}
+
+#[derive(Debug)]
+/// A statement that is waiting for the next assignment of a variable
+struct Wait {
+    name: String,
+    /// Variables that were already assigned, if the statement waits for more than one
+    resolved: Vec<String>,
+    /// Id of the scope the statement was suspended in
+    scope: usize,
+    statement: Statement,
+}
+
 #[derive(Debug, Default)]
 pub struct VariableState(pub HashMap<String, Variable>);
 
 impl VariableState {
-    pub fn declare_var(
-        &mut self,
-        name: &str,
-        value: Value,
-        line: usize,
-        type_: VarType,
-        life_time: Option<LifeTime>,
-    ) {
-        self.0.insert(
-            name.to_string(),
-            Variable {
-                previous: Value::Undefined,
-                value,
-                line,
-                type_,
-                life_time,
-                create_time: if matches!(life_time, Some(LifeTime::Seconds(_))) {
-                    Some(Instant::now())
-                } else {
-                    None
-                },
-            },
-        );
+    pub fn declare_var(&mut self, name: &str, value: Value, line: usize, type_: VarType) {
+        self.declare(name, Variable::new(value, line, type_));
     }
 
-    /// Checks and removes any variables that has expired
-    pub fn validate_lifetime(&mut self) {
-        self.0.retain(|_, var| {
-            let Some(LifeTime::Seconds(seconds)) = var.life_time else {
-                return true;
-            };
+    /// Declares the variable, which keeps the variable it replaces if it expires so it can be restored
+    pub fn declare(&mut self, name: &str, mut var: Variable) {
+        if var.timer.is_some() {
+            var.shadowed = self.0.remove(name).map(Box::new);
+        }
+        self.0.insert(name.to_string(), var);
+    }
 
-            let create_time = var.create_time.unwrap();
-            let now = Instant::now();
-            let duration = now.duration_since(create_time).as_secs_f64();
-            duration < seconds
-        });
+    /// Removes the variable with the timer, restoring the variable it replaced
+    /// # Returns
+    /// - `Some(true)` if the expired variable was the one the name refers to, instead of one that was replaced
+    /// - `None` if the variable isn't in this scope
+    pub fn expire(&mut self, name: &str, timer: TimerId) -> Option<bool> {
+        let var = self.0.get_mut(name)?;
+        if var.timer != Some(timer) {
+            return var.expire_shadowed(timer).then_some(false);
+        }
+
+        match var.shadowed.take() {
+            Some(shadowed) => *var = *shadowed,
+            None => {
+                self.0.remove(name);
+            }
+        }
+        Some(true)
     }
 
     pub fn get_var(&self, name: &str) -> Option<&Variable> {
         self.0.get(name)
     }
 
-    pub fn set_var(
-        &mut self,
-        name: &str,
-        args: PosWithInfo,
-        postfix: &[AtomPostfix],
-        value: &Value,
-    ) -> Result<bool, Error> {
+    pub fn set_var(&mut self, name: &str, args: PosWithInfo, value: &Value) -> bool {
         if let Some(var) = self.0.get_mut(name) {
-            var.set_value(args, value.clone(), postfix)?;
-            Ok(true)
+            var.set_value(args, value.clone());
+            true
         } else {
-            Ok(false)
+            false
         }
     }
 }
@@ -481,40 +1350,83 @@ impl VariableState {
 #[derive(Debug, Clone)]
 pub struct Variable {
     value: Value,
-    previous: Value,
+    history: History,
     line: usize,
     type_: VarType,
-    life_time: Option<LifeTime>,
-    create_time: Option<Instant>,
+    // expires the variable if its lifetime is in seconds
+    timer: Option<TimerId>,
+    // variable of the same scope that this replaced, which is restored when this expires
+    shadowed: Option<Box<Variable>>,
 }
 
 impl Variable {
+    pub fn new(value: Value, line: usize, type_: VarType) -> Self {
+        Self {
+            history: History::default(),
+            value,
+            line,
+            type_,
+            timer: None,
+            shadowed: None,
+        }
+    }
+
+    /// Removes the replaced variable with the timer, keeping the ones it replaced
+    fn expire_shadowed(&mut self, timer: TimerId) -> bool {
+        let Some(shadowed) = self.shadowed.as_mut() else {
+            return false;
+        };
+        if shadowed.timer != Some(timer) {
+            return shadowed.expire_shadowed(timer);
+        }
+
+        self.shadowed = shadowed.shadowed.take();
+        true
+    }
+
     pub fn get_value(&self) -> &Value {
         &self.value
     }
 
-    pub fn get_previous_value(&self) -> &Value {
-        &self.previous
+    /// Gets the value from `steps` assignments ago, where 0 is the current value
+    pub fn get_value_at(&self, steps: usize) -> Value {
+        match steps {
+            0 => self.value.clone(),
+            _ => self.history.get(steps),
+        }
+    }
+
+    /// If the variable can be assigned another value, which is the case for `var const` and `var var`
+    pub fn is_reassignable(&self) -> bool {
+        matches!(self.type_, VarType::VarConst | VarType::VarVar)
     }
 
-    pub fn set_value(
-        &mut self,
+    /// If the properties of the value can be set, which is the case for `const var` and `var var`
+    pub fn is_mutable(&self) -> bool {
+        matches!(self.type_, VarType::ConstVar | VarType::VarVar)
+    }
+
+    /// Assigns the value, which is ignored if the variable can't be reassigned
+    /// - Strict mode checks this with [`InterpreterState::check_write`] beforehand
+    pub fn set_value(&mut self, args: PosWithInfo, value: Value) {
+        let history_len = args.extra.0.state.history_len.load(Ordering::Relaxed);
+
+        if self.is_reassignable() {
+            let previous = std::mem::replace(&mut self.value, value);
+            self.history.push(previous, history_len);
+        }
+    }
+
+    /// Sets the property at the end of `postfix` on the object the variable holds
+    pub fn set_property(
+        &self,
         args: PosWithInfo,
         value: Value,
         postfix: &[AtomPostfix],
     ) -> Result<(), Error> {
-        if postfix.is_empty() {
-            // TODO: concrete error?
-            if matches!(self.type_, VarType::VarConst | VarType::VarVar) {
-                self.previous = self.value.clone();
-                self.value = value;
-            }
-            return Ok(());
-        }
+        let history_len = args.extra.0.state.history_len.load(Ordering::Relaxed);
 
-        // below is for postfix operations, this only applies to const var and var var
-        if !matches!(self.type_, VarType::ConstVar | VarType::VarVar) {
-            // TODO: concrete error?
+        if !self.is_mutable() {
             return Ok(());
         }
 
@@ -534,20 +1446,10 @@ impl Variable {
             return Err(Error::Type("Cannot read properties of null".to_string()));
         };
 
-        let mut var = var.lock().unwrap();
-
-        self.previous = self.value.clone();
-        // TODO: reuse code from postfix
-        match postfix_last {
-            AtomPostfix::DotNotation(identifier) => var.set_property(identifier, value),
-            AtomPostfix::BracketNotation(expr) => {
-                if let Value::String(key) = expr.eval(args)?.0.borrow() {
-                    var.set_property(key, value)
-                } else {
-                    todo!()
-                }
-            }
-        }
+        let key = postfix_last.key(args)?;
+        var.lock()
+            .unwrap()
+            .set_property_tracked(key, value, history_len);
 
         Ok(())
     }
@@ -557,6 +1459,40 @@ impl Variable {
 /// A stack of function states
 pub struct Functions(pub Vec<FunctionState>);
 
+#[derive(Debug, Clone)]
+/// Class state, which is the extra information the class object doesn't have
+pub struct ClassState {
+    obj: Weak<Mutex<Object>>,
+    body: Arc<ClassBody>,
+    /// A class can only be instantiated once
+    instantiated: Arc<AtomicBool>,
+}
+
+impl ClassState {
+    pub fn new(obj: &ObjectRef, body: Arc<ClassBody>) -> Self {
+        Self {
+            obj: Arc::downgrade(obj),
+            body,
+            instantiated: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn obj(&self) -> Option<ObjectRef> {
+        self.obj.upgrade()
+    }
+
+    pub fn body(&self) -> &ClassBody {
+        &self.body
+    }
+
+    /// Marks the class as instantiated
+    /// # Returns
+    /// - `false` if it was already instantiated
+    pub fn take_instance(&self) -> bool {
+        !self.instantiated.swap(true, Ordering::Relaxed)
+    }
+}
+
 #[derive(Debug, Clone)]
 /// Function state
 /// - A function is either
@@ -564,12 +1500,51 @@ pub struct Functions(pub Vec<FunctionState>);
 ///   - it is a native function
 pub struct FunctionState {
     pub arg_count: Option<usize>,
+    /// How many of the arguments a call has to pass, the ones after them can be left out
+    pub required_args: usize,
     variant: FunctionVariant,
     obj: Weak<Mutex<Object>>,
 }
 
 impl FunctionState {
-    fn eval(&self, eval_args: PosWithInfo, args: Vec<Wrapper<Cow<Value>>>) -> Result<Value, Error> {
+    /// Binds the function to the object
+    /// - A function that accepts any number of arguments doesn't need any
+    pub fn new(obj: &ObjectRef, variant: FunctionVariant, arg_count: Option<usize>) -> Self {
+        // a combined signal function reads without its argument
+        let optional = matches!(
+            variant,
+            FunctionVariant::Signal {
+                access: SignalAccess::GetOrSet,
+                ..
+            }
+        );
+        let required_args = match arg_count {
+            Some(arg_count) if !optional => arg_count,
+            _ => 0,
+        };
+        Self {
+            arg_count,
+            required_args,
+            variant,
+            obj: Arc::downgrade(obj),
+        }
+    }
+
+    /// Lets calls leave out the arguments after the first `required_args`
+    pub fn with_required_args(mut self, required_args: usize) -> Self {
+        self.required_args = required_args;
+        self
+    }
+
+    /// # Arguments
+    /// - `this`: the object the function is called on, for methods
+    fn eval(
+        &self,
+        eval_args: PosWithInfo,
+        frame: StackFrame,
+        this: Option<Value>,
+        args: Vec<Wrapper<Cow<Value>>>,
+    ) -> Result<Value, Error> {
         let interpreter = eval_args.extra.0;
         let state = &interpreter.state;
 
@@ -577,96 +1552,261 @@ impl FunctionState {
             FunctionVariant::FunctionDefined {
                 body,
                 arg_names,
-                body_line: _,
+                body_line,
+                body_column,
+                cache,
+                is_async,
             } => {
+                let depth = state.scope_stacks.lock().unwrap().len();
+                state.budget.call(depth)?;
+
                 // gc should be done up to this point, so it should be safe
                 let obj = self.obj.upgrade().unwrap();
-                let mut obj = obj.lock().unwrap();
 
-                obj.set_property("arguments", array::constructor(interpreter, args)?);
-                let Value::Object(Some(args)) = obj.get_property("arguments").unwrap() else {
-                    unreachable!();
+                // the lock is only held here, since the body could call this function again
+                let args = {
+                    let mut obj = obj.lock().unwrap();
+                    obj.set_property("arguments", array::constructor(interpreter, args)?);
+                    let Value::Object(Some(args)) = obj.get_property("arguments").unwrap() else {
+                        unreachable!();
+                    };
+                    let args = args.lock().unwrap();
+                    args.array_obj_iter().collect::<Vec<_>>()
                 };
-                let args = args.lock().unwrap();
 
-                let pop_call_stack = || {
-                    state.scope_stacks.lock().unwrap().pop();
+                let declare = || {
+                    for (arg_name, arg_value) in arg_names.iter().zip(args) {
+                        state.add_var(&arg_name.to_string(), arg_value, 0, VarType::VarVar, None);
+                    }
+                    if let Some(this) = this {
+                        state.add_var("this", this, 0, VarType::VarVar, None);
+                    }
                 };
 
-                state.scope_stacks.lock().unwrap().push(vec![ScopeState {
-                    vars: VariableState::default(),
-                    whens: Vec::new(),
-                }]);
+                // body keeps the position it was defined at, so errors point at the right place
+                let code_with_pos = Position {
+                    line: *body_line,
+                    column: *body_column,
+                    index: 0,
+                    input: body.as_str(),
+                    extra: eval_args.extra,
+                };
 
-                // declare arguments
-                for (arg_name, arg_value) in arg_names.iter().zip(args.array_obj_iter()) {
-                    state.add_var(&arg_name.to_string(), arg_value, 0, VarType::VarVar, None);
+                if *is_async {
+                    state.spawn_task(frame, declare, body, cache, code_with_pos);
+                    return Ok(Value::Undefined);
                 }
 
-                let code_with_pos = Position::new_with_extra(body.as_str(), eval_args.extra);
-
-                // check if block
-                if let Ok((mut code_with_pos, Statement::ScopeStart(_))) =
-                    Statement::parse(code_with_pos)
-                {
-                    let mut scope_count = 1usize;
-
-                    // its a block
-                    while let Ok((code_after, statement)) = Statement::parse(code_with_pos) {
-                        match statement {
-                            Statement::ScopeStart(_) => {
-                                scope_count =
-                                    scope_count.checked_add(1).expect("scope count overflow")
-                            }
-                            Statement::ScopeEnd(_) => {
-                                scope_count -= 1;
-                                if scope_count == 0 {
-                                    break;
-                                }
-                            }
-                            _ => (),
-                        }
+                state.call_frame(frame, || {
+                    declare();
+                    Self::eval_body(cache, code_with_pos, eval_args)
+                })
+            }
+            FunctionVariant::Native(native) => native(interpreter, args),
+            FunctionVariant::Method(method) | FunctionVariant::MutatingMethod(method) => {
+                method(eval_args, this.unwrap_or(Value::Undefined), args)
+            }
+            FunctionVariant::Signal { signal, access } => {
+                let set = match access {
+                    SignalAccess::Get => false,
+                    SignalAccess::Set => true,
+                    SignalAccess::GetOrSet => !args.is_empty(),
+                };
+                if !set {
+                    return Ok(signal.get());
+                }
+
+                let value = args
+                    .first()
+                    .map(|value| value.as_ref().clone())
+                    .unwrap_or(Value::Undefined);
+                signal.set(value.clone());
+                state.update_signal(eval_args, signal, value)?;
+                Ok(Value::Undefined)
+            }
+        }
+    }
 
-                        code_with_pos = code_after;
-                        let ret = match statement.eval(code_with_pos) {
-                            Ok(ret) => ret.return_value,
-                            Err(err) => return Err(err),
-                        };
-                        if let Some(ret) = ret {
-                            pop_call_stack();
-                            return Ok(ret);
+    fn eval_body(
+        cache: &BodyCache,
+        code_with_pos: PosWithInfo,
+        eval_args: PosWithInfo,
+    ) -> Result<Value, Error> {
+        // check if block
+        let block_start = cache
+            .statement(code_with_pos)
+            .ok()
+            .filter(|(_, statement)| matches!(**statement, Statement::ScopeStart(_)));
+        if let Some((mut code_with_pos, _)) = block_start {
+            let mut scope_count = 1usize;
+
+            // its a block
+            while let Ok((code_after, statement)) = cache.statement(code_with_pos) {
+                match *statement {
+                    Statement::ScopeStart(_) => {
+                        scope_count = scope_count.checked_add(1).expect("scope count overflow")
+                    }
+                    Statement::ScopeEnd(_) => {
+                        scope_count -= 1;
+                        if scope_count == 0 {
+                            break;
                         }
                     }
-
-                    pop_call_stack();
-                    return Ok(Value::Undefined);
+                    _ => (),
                 }
 
-                // expression (this won't fail because implicit strings)
-                if let Ok((_, expression)) = Expression::parse(code_with_pos) {
-                    let value = expression.eval(eval_args)?;
-                    pop_call_stack();
-                    return Ok(value.0.into_owned());
+                let ret = statement
+                    .eval_traced(code_with_pos, code_after)?
+                    .return_value;
+                code_with_pos = code_after;
+                if let Some(ret) = ret {
+                    return Ok(ret);
                 }
+            }
 
-                unreachable!("function body is not a block or expression, which should be impossible because of implicit strings");
+            return Ok(Value::Undefined);
+        }
+
+        // expression (this won't fail because implicit strings)
+        if let Ok((_, expression)) = cache.expression(code_with_pos) {
+            let value = expression.eval(eval_args).inspect_err(|_| {
+                eval_args.extra.0.state.trace_error(code_with_pos.into());
+            })?;
+            return Ok(value.0.into_owned());
+        }
+
+        unreachable!("function body is not a block or expression, which should be impossible because of implicit strings");
+    }
+}
+
+#[derive(Debug)]
+/// An async function call, which runs one statement of its body per turn
+struct Task {
+    frame: CallFrame,
+    body: Arc<String>,
+    cache: Arc<BodyCache>,
+    /// Index, line and column of the next statement
+    next: (usize, usize, usize),
+    /// Number of scopes open in the body, `None` if the body is an expression
+    scope_count: Option<usize>,
+}
+
+impl Task {
+    /// Runs the next statement with the scopes of the call
+    /// # Returns
+    /// - If the call has finished
+    fn step(&mut self, state: &InterpreterState, args: PosWithInfo) -> Result<bool, Error> {
+        state
+            .scope_stacks
+            .lock()
+            .unwrap()
+            .push(std::mem::take(&mut self.frame));
+        let res = self.eval_next(args);
+        self.frame = state.scope_stacks.lock().unwrap().pop().unwrap();
+        res
+    }
+
+    fn eval_next(&mut self, args: PosWithInfo) -> Result<bool, Error> {
+        let (index, line, column) = self.next;
+        let body = Arc::clone(&self.body);
+        let code_with_pos = Position {
+            line,
+            column,
+            index,
+            input: &body[index..],
+            extra: args.extra,
+        };
+
+        let Some(scope_count) = &mut self.scope_count else {
+            FunctionState::eval_body(&self.cache, code_with_pos, code_with_pos)?;
+            return Ok(true);
+        };
+
+        let Ok((code_after, statement)) = self.cache.statement(code_with_pos) else {
+            return Ok(true);
+        };
+        match *statement {
+            Statement::ScopeStart(_) => *scope_count += 1,
+            Statement::ScopeEnd(_) => {
+                *scope_count -= 1;
+                if *scope_count == 0 {
+                    return Ok(true);
+                }
             }
-            FunctionVariant::Native(native) => native(interpreter, args),
+            _ => (),
         }
+
+        let ret = statement
+            .eval_traced(code_with_pos, code_after)?
+            .return_value;
+        self.next = (code_after.index, code_after.line, code_after.column);
+        Ok(ret.is_some())
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub enum FunctionVariant {
     FunctionDefined {
         /// The line where the function is usable from
         body_line: usize,
+        /// The column where the body starts
+        body_column: usize,
         /// Where the expression / scope is located as an index
         body: Arc<String>,
         /// Argument names
         arg_names: Arc<Vec<String>>,
+        /// Parsed body, which is shared with the definition
+        cache: Arc<BodyCache>,
+        /// Calls run as tasks that take turns with the main program
+        is_async: bool,
     },
     Native(NativeFunc),
+    /// Native function that is called on an object, like the methods of `Array.prototype`
+    Method(NativeMethod),
+    /// Method that changes the object it's called on, like `push`
+    /// - Calling it on a variable is a write to the variable, so it's ignored for constants and triggers `when` watchers
+    MutatingMethod(NativeMethod),
+    /// Function of a signal made by `use`
+    Signal {
+        signal: Arc<Signal>,
+        access: SignalAccess,
+    },
 }
 
-pub type NativeFunc = fn(&Interpreter, Vec<Wrapper<Cow<Value>>>) -> Result<Value, Error>;
+impl Debug for FunctionVariant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::FunctionDefined {
+                body_line,
+                body_column,
+                body,
+                arg_names,
+                ..
+            } => f
+                .debug_struct("FunctionDefined")
+                .field("body_line", body_line)
+                .field("body_column", body_column)
+                .field("body", body)
+                .field("arg_names", arg_names)
+                .finish(),
+            Self::Native(_) => f.debug_tuple("Native").finish_non_exhaustive(),
+            Self::Method(_) => f.debug_tuple("Method").finish_non_exhaustive(),
+            Self::MutatingMethod(_) => f.debug_tuple("MutatingMethod").finish_non_exhaustive(),
+            Self::Signal { signal, access } => f
+                .debug_struct("Signal")
+                .field("signal", signal)
+                .field("access", access)
+                .finish(),
+        }
+    }
+}
+
+/// A function implemented in rust
+/// - This can capture state, as long as it can be shared between threads
+pub type NativeFunc =
+    Arc<dyn Fn(&Interpreter, Vec<Wrapper<Cow<Value>>>) -> Result<Value, Error> + Send + Sync>;
+
+/// A method implemented in rust, which gets `this` as the second argument
+/// - `this` is `undefined` if the function isn't called on an object
+pub type NativeMethod =
+    Arc<dyn Fn(PosWithInfo, Value, Vec<Wrapper<Cow<Value>>>) -> Result<Value, Error> + Send + Sync>;
@@ -1,37 +1,52 @@
 //! Module containing the standard library functions
 
-use crate::{interpreter::runtime::state::FunctionVariant, Interpreter};
+use std::{borrow::Cow, sync::Arc};
 
-use super::state::NativeFunc;
+use crate::{
+    interpreter::runtime::{state::FunctionVariant, value::Value, Error},
+    prelude::Wrapper,
+    Interpreter,
+};
 
 pub mod array;
 mod debug;
 pub mod function;
 mod info;
 pub mod object;
+pub mod signal;
 mod stdio;
+mod symbol;
 mod thread;
 
 pub fn load(interpreter: &Interpreter) {
     // funcs
-    let funcs: Vec<(_, _, NativeFunc)> = vec![
+    type Func = fn(&Interpreter, Vec<Wrapper<Cow<Value>>>) -> Result<Value, Error>;
+    let funcs: Vec<(_, _, Func)> = vec![
         ("assert", 1, debug::assert),
         ("print", 1, stdio::print),
         ("input", 1, stdio::input),
         ("typeof", 1, info::get_typeof),
-        ("sleep", 1, thread::sleep),
+        ("noop", 0, thread::noop),
+        ("use", 1, signal::use_signal),
     ];
 
     for func in funcs {
         let (name, arg_count, func) = func;
         interpreter.state.add_func_declare_var(
             name,
-            FunctionVariant::Native(func),
+            FunctionVariant::Native(Arc::new(func)),
             Some(arg_count),
         );
     }
+    // `sleep` triggers the `when` watchers of variables that expire while it waits, which needs where it was called
+    interpreter.state.add_func_declare_var(
+        "sleep",
+        FunctionVariant::Method(Arc::new(thread::sleep)),
+        Some(1),
+    );
 
     object::load(interpreter);
     function::load(interpreter);
     array::load(interpreter);
+    symbol::load(interpreter);
 }
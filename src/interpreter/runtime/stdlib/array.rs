@@ -1,4 +1,3 @@
-use lazy_static::lazy_static;
 use std::{
     borrow::Cow,
     collections::HashMap,
@@ -7,54 +6,424 @@ use std::{
 
 use crate::{
     interpreter::evaluators::variable::VarType,
+    parsers::PosWithInfo,
     prelude::Wrapper,
     runtime::{
-        state::FunctionVariant,
-        value::{Object, ObjectRef, Value, PROTO_PROP},
+        error::StackFrame,
+        realm::Realm,
+        state::{FunctionState, FunctionVariant},
+        value::{Hint, Object, ObjectRef, PropertyKey, Value, PROTO_PROP},
         Error,
     },
     Interpreter,
 };
 
-lazy_static! {
-    pub static ref PROTOTYPE: ObjectRef = {
-        let array_proto = Object::new(HashMap::new());
+type Args<'a> = Vec<Wrapper<Cow<'a, Value>>>;
+type Method = fn(PosWithInfo, Value, Args) -> Result<Value, Error>;
 
-        Arc::new(Mutex::new(array_proto))
-    };
+/// Creates `Array.prototype`
+/// # Returns
+/// - The prototype, and the functions its methods are bound to
+pub fn prototype(
+    object_prototype: &ObjectRef,
+    function_prototype: &ObjectRef,
+) -> (ObjectRef, Vec<FunctionState>) {
+    // how many arguments have to be passed, how many it takes, and whether the method changes the array
+    let methods: Vec<(_, _, _, _, Method)> = vec![
+        ("push", 1, 1, true, push),
+        ("pop", 0, 0, true, pop),
+        ("shift", 0, 0, true, shift),
+        ("unshift", 1, 1, true, unshift),
+        ("slice", 0, 2, false, slice),
+        ("splice", 1, 3, true, splice),
+        ("indexOf", 1, 1, false, index_of),
+        ("includes", 1, 1, false, includes),
+        ("join", 0, 1, false, join),
+        ("reverse", 0, 0, true, reverse),
+        ("map", 1, 1, false, map),
+        ("filter", 1, 1, false, filter),
+        ("reduce", 1, 2, false, reduce),
+        ("forEach", 1, 1, false, for_each),
+    ];
+
+    let mut props = HashMap::from([(PROTO_PROP.to_string(), Arc::clone(object_prototype).into())]);
+    let mut funcs = Vec::new();
+    for (name, required_args, arg_count, mutates, method) in methods {
+        let obj = Arc::new(Mutex::new(Object::new_empty(HashMap::from([(
+            PROTO_PROP.to_string(),
+            Arc::clone(function_prototype).into(),
+        )]))));
+        let variant = if mutates {
+            FunctionVariant::MutatingMethod(Arc::new(method))
+        } else {
+            FunctionVariant::Method(Arc::new(method))
+        };
+        funcs.push(
+            FunctionState::new(&obj, variant, Some(arg_count)).with_required_args(required_args),
+        );
+        props.insert(name.to_string(), obj.into());
+    }
+
+    let mut prototype = Object::new_empty(props);
+    prototype.hide_methods();
+    (Arc::new(Mutex::new(prototype)), funcs)
 }
 
 pub fn constructor(
-    _interpreter: &Interpreter,
+    interpreter: &Interpreter,
     args: Vec<Wrapper<Cow<Value>>>,
 ) -> Result<Value, Error> {
-    let mut props = HashMap::from([(PROTO_PROP.to_string(), Arc::clone(&PROTOTYPE).into())]);
+    let items = args.into_iter().map(|item| item.0.into_owned()).collect();
+    Ok(from_items(interpreter, items))
+}
+
+/// Creates an array, the first item is at `-1`
+pub fn from_items(interpreter: &Interpreter, items: Vec<Value>) -> Value {
+    let realm = &interpreter.state.realm;
+    let mut props = HashMap::from([(
+        PROTO_PROP.to_string(),
+        Arc::clone(&realm.array_prototype).into(),
+    )]);
+    for (position, item) in items.into_iter().enumerate() {
+        props.insert(key(position), item);
+    }
+
+    interpreter
+        .state
+        .heap
+        .alloc(Object::new(realm, props))
+        .into()
+}
+
+/// Gets a property every array has without it being set, which is `length`
+pub fn property(realm: &Realm, obj: &Object, key: &PropertyKey) -> Option<Value> {
+    if !matches!(key, PropertyKey::String(key) if key == "length") {
+        return None;
+    }
+    let Some(Value::Object(Some(proto))) = obj.get_property(PROTO_PROP) else {
+        return None;
+    };
+    if !Arc::ptr_eq(&proto, &realm.array_prototype) {
+        return None;
+    }
+
+    Some(Value::Number(obj.array_obj_iter().count() as f64))
+}
+
+/// Key of the item at the position, where the first item is at `-1`
+fn key(position: usize) -> String {
+    (position as isize - 1).to_string()
+}
+
+/// Gets the object a method is called on
+fn this_obj(this: &Value, method: &str) -> Result<ObjectRef, Error> {
+    match this {
+        Value::Object(Some(obj)) => Ok(Arc::clone(obj)),
+        _ => Err(Error::Type(format!(
+            "Array.prototype.{method} called on {this}"
+        ))),
+    }
+}
+
+fn items(obj: &ObjectRef) -> Vec<Value> {
+    obj.lock().unwrap().array_obj_iter().collect()
+}
+
+/// Replaces the items, which are renumbered from `-1` so fractional indices become whole again
+fn set_items(obj: &ObjectRef, items: Vec<Value>) {
+    let mut obj = obj.lock().unwrap();
+    for key in obj.array_keys() {
+        obj.remove_property(&key);
+    }
+    for (position, item) in items.into_iter().enumerate() {
+        obj.set_property(&key(position), item);
+    }
+}
+
+fn arg(args: &Args, index: usize) -> Value {
+    args.get(index)
+        .map(|arg| arg.as_ref().clone())
+        .unwrap_or(Value::Undefined)
+}
+
+/// Converts an index to a position in the items, clamped to `0..=len`
+/// - `-1` is the first item, and indices before it count back from the end, so `-2` is the last item
+/// - `undefined` is the default position
+fn position(index: &Value, len: usize, default: usize) -> Result<usize, Error> {
+    if matches!(index, Value::Undefined) {
+        return Ok(default);
+    }
+    let index = f64::try_from(index)?;
+    let index = if index.is_nan() {
+        0.0
+    } else {
+        index.trunc() + 1.0
+    };
+    let position = if index < 0.0 {
+        (len as f64 + index).max(0.0)
+    } else {
+        index.min(len as f64)
+    };
+
+    Ok(position as usize)
+}
+
+/// Calls the callback with the item, its index and the array
+fn call(
+    args: PosWithInfo,
+    method: &str,
+    callback: &Value,
+    call_args: Vec<Value>,
+) -> Result<Value, Error> {
+    let frame = StackFrame {
+        name: method.to_string(),
+        call_site: args.into(),
+    };
+    let call_args = call_args
+        .into_iter()
+        .map(|arg| Wrapper(Cow::Owned(arg)))
+        .collect();
+    args.extra
+        .0
+        .state
+        .call_value(args, frame, callback, Value::Undefined, call_args)
+        .ok_or_else(|| Error::Type(format!("{callback} is not a function")))?
+}
+
+/// Calls the callback for each item
+/// # Returns
+/// - The items with what the callback returned for them
+fn call_each(
+    args: PosWithInfo,
+    method: &str,
+    this: &Value,
+    callback: &Value,
+) -> Result<Vec<(Value, Value)>, Error> {
+    let items = items(&this_obj(this, method)?);
+    let mut results = Vec::with_capacity(items.len());
+    for (position, item) in items.into_iter().enumerate() {
+        let index = Value::Number(position as f64 - 1.0);
+        let result = call(
+            args,
+            method,
+            callback,
+            vec![item.clone(), index, this.clone()],
+        )?;
+        results.push((item, result));
+    }
+
+    Ok(results)
+}
+
+/// Adds the item to the end
+/// # Returns
+/// - The new length
+fn push(_args: PosWithInfo, this: Value, args: Args) -> Result<Value, Error> {
+    let obj = this_obj(&this, "push")?;
+    let mut items = items(&obj);
+    items.push(arg(&args, 0));
+    let len = items.len();
+    set_items(&obj, items);
 
-    if let Some(first) = args.first() {
-        let first = first.as_ref();
-        props.insert("-1".to_string(), first.clone());
+    Ok(Value::Number(len as f64))
+}
+
+/// Removes the last item and returns it
+fn pop(_args: PosWithInfo, this: Value, _: Args) -> Result<Value, Error> {
+    let obj = this_obj(&this, "pop")?;
+    let mut items = items(&obj);
+    let item = items.pop();
+    set_items(&obj, items);
+
+    Ok(item.unwrap_or(Value::Undefined))
+}
+
+/// Removes the first item and returns it, the rest move down an index
+fn shift(_args: PosWithInfo, this: Value, _: Args) -> Result<Value, Error> {
+    let obj = this_obj(&this, "shift")?;
+    let mut items = items(&obj);
+    if items.is_empty() {
+        return Ok(Value::Undefined);
+    }
+    let item = items.remove(0);
+    set_items(&obj, items);
+
+    Ok(item)
+}
+
+/// Adds the item to the start, the rest move up an index
+/// # Returns
+/// - The new length
+fn unshift(_args: PosWithInfo, this: Value, args: Args) -> Result<Value, Error> {
+    let obj = this_obj(&this, "unshift")?;
+    let mut items = items(&obj);
+    items.insert(0, arg(&args, 0));
+    let len = items.len();
+    set_items(&obj, items);
+
+    Ok(Value::Number(len as f64))
+}
+
+/// Copies the items from the start index up to the end index, which isn't included
+/// - Leaving out the start copies from the first item
+/// - Leaving out the end, or `undefined` as the end, copies up to the last item
+fn slice(args: PosWithInfo, this: Value, call_args: Args) -> Result<Value, Error> {
+    let items = items(&this_obj(&this, "slice")?);
+    let start = position(&arg(&call_args, 0), items.len(), 0)?;
+    let end = position(&arg(&call_args, 1), items.len(), items.len())?.max(start);
+
+    Ok(from_items(args.extra.0, items[start..end].to_vec()))
+}
+
+/// Removes the number of items from the start index, and inserts the items of the array in their place
+/// - Leaving out the number removes every item after the start index
+/// - The items to insert can also be a single item, or left out or `undefined` to insert nothing
+/// # Returns
+/// - The removed items
+fn splice(args: PosWithInfo, this: Value, call_args: Args) -> Result<Value, Error> {
+    let obj = this_obj(&this, "splice")?;
+    let mut items = items(&obj);
+    let start = position(&arg(&call_args, 0), items.len(), 0)?;
+    let count = match arg(&call_args, 1) {
+        Value::Undefined => items.len() - start,
+        count => (f64::try_from(count)?.max(0.0) as usize).min(items.len() - start),
+    };
+    let inserted = match arg(&call_args, 2) {
+        Value::Undefined => Vec::new(),
+        Value::Object(Some(inserted)) => self::items(&inserted),
+        item => vec![item],
+    };
+
+    let removed = items.splice(start..start + count, inserted).collect();
+    set_items(&obj, items);
+
+    Ok(from_items(args.extra.0, removed))
+}
+
+/// Index of the first item that is strictly equal to the value, `undefined` if there is none
+/// - `-1` can't mean not found, since it's the index of the first item
+fn index_of(_args: PosWithInfo, this: Value, args: Args) -> Result<Value, Error> {
+    let value = arg(&args, 0);
+    let index = items(&this_obj(&this, "indexOf")?)
+        .iter()
+        .position(|item| item.strict_eq(&value))
+        .map(|position| Value::Number(position as f64 - 1.0));
+
+    Ok(index.unwrap_or(Value::Undefined))
+}
+
+/// If an item is strictly equal to the value, where `NaN` is equal to itself
+fn includes(_args: PosWithInfo, this: Value, args: Args) -> Result<Value, Error> {
+    let value = arg(&args, 0);
+    let is_nan = |value: &Value| matches!(value, Value::Number(num) if num.is_nan());
+    let found = items(&this_obj(&this, "includes")?)
+        .iter()
+        .any(|item| item.strict_eq(&value) || (is_nan(item) && is_nan(&value)));
+
+    Ok(Value::Boolean(found))
+}
+
+/// Joins the items as strings with the separator, which is `,` if it's left out or `undefined`
+/// - `undefined` and `null` items are empty strings
+fn join(args: PosWithInfo, this: Value, call_args: Args) -> Result<Value, Error> {
+    let items = items(&this_obj(&this, "join")?);
+    let separator = match arg(&call_args, 0) {
+        Value::Undefined => ",".to_string(),
+        separator => separator
+            .to_primitive(args, Hint::String)?
+            .coerce_string()?,
+    };
+
+    let mut strings = Vec::with_capacity(items.len());
+    for item in items {
+        let string = match item {
+            Value::Undefined | Value::Object(None) => String::new(),
+            item => item.to_primitive(args, Hint::String)?.coerce_string()?,
+        };
+        strings.push(string);
     }
-    for (i, item) in args.iter().enumerate().skip(1) {
-        let item = item.as_ref().clone();
-        props.insert((i - 1).to_string(), item);
+
+    Ok(Value::String(strings.join(&separator)))
+}
+
+/// Reverses the items in place
+/// # Returns
+/// - The array itself
+fn reverse(_args: PosWithInfo, this: Value, _: Args) -> Result<Value, Error> {
+    let obj = this_obj(&this, "reverse")?;
+    let mut items = items(&obj);
+    items.reverse();
+    set_items(&obj, items);
+
+    Ok(this)
+}
+
+/// Creates an array of what the callback returns for each item
+fn map(args: PosWithInfo, this: Value, call_args: Args) -> Result<Value, Error> {
+    let results = call_each(args, "map", &this, &arg(&call_args, 0))?;
+    let items = results.into_iter().map(|(_, result)| result).collect();
+
+    Ok(from_items(args.extra.0, items))
+}
+
+/// Creates an array of the items the callback returns a truthy value for
+fn filter(args: PosWithInfo, this: Value, call_args: Args) -> Result<Value, Error> {
+    let results = call_each(args, "filter", &this, &arg(&call_args, 0))?;
+    let items = results
+        .into_iter()
+        .filter(|(_, result)| bool::from(result))
+        .map(|(item, _)| item)
+        .collect();
+
+    Ok(from_items(args.extra.0, items))
+}
+
+/// Calls the callback with the accumulator and each item, the result is the next accumulator
+/// - The first item is the initial accumulator if the initial value is left out or `undefined`
+fn reduce(args: PosWithInfo, this: Value, call_args: Args) -> Result<Value, Error> {
+    let callback = arg(&call_args, 0);
+    let mut items = items(&this_obj(&this, "reduce")?)
+        .into_iter()
+        .enumerate()
+        .map(|(position, item)| (Value::Number(position as f64 - 1.0), item));
+
+    let mut acc = match arg(&call_args, 1) {
+        Value::Undefined => items.next().map(|(_, item)| item).ok_or_else(|| {
+            Error::Type("Reduce of empty array with no initial value".to_string())
+        })?,
+        initial => initial,
+    };
+    for (index, item) in items {
+        acc = call(
+            args,
+            "reduce",
+            &callback,
+            vec![acc, item, index, this.clone()],
+        )?;
     }
 
-    let obj = Object::new(props);
+    Ok(acc)
+}
 
-    Ok(obj.into())
+/// Calls the callback for each item
+fn for_each(args: PosWithInfo, this: Value, call_args: Args) -> Result<Value, Error> {
+    call_each(args, "forEach", &this, &arg(&call_args, 0))?;
+    Ok(Value::Undefined)
 }
 
 pub fn load(interpreter: &Interpreter) {
     // Array
     let array = Object::new_empty(HashMap::from([(
         "prototype".to_string(),
-        Arc::clone(&PROTOTYPE).into(),
+        Arc::clone(&interpreter.state.realm.array_prototype).into(),
     )]));
 
     interpreter
         .state
         .add_var("Array", array.into(), 0, VarType::VarVar, None);
-    interpreter
-        .state
-        .add_func_declare_var("Array", FunctionVariant::Native(constructor), None);
+    interpreter.state.add_func_declare_var(
+        "Array",
+        FunctionVariant::Native(Arc::new(constructor)),
+        None,
+    );
 }
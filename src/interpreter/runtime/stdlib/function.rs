@@ -3,28 +3,27 @@ use std::{
     sync::{Arc, Mutex},
 };
 
-use lazy_static::lazy_static;
-
 use crate::{
     interpreter::evaluators::variable::VarType,
-    runtime::value::{Object, ObjectRef, Value},
+    runtime::value::{Object, ObjectRef, Value, PROTO_PROP},
     Interpreter,
 };
 
-lazy_static! {
-    pub static ref PROTOTYPE: ObjectRef = {
-        // Function.prototype
-        let func_proto = Object::new(HashMap::from([("arguments".to_string(), Value::Object(None))]));
+/// Creates `Function.prototype`
+pub fn prototype(object_prototype: &ObjectRef) -> ObjectRef {
+    let func_proto = Object::new_empty(HashMap::from([
+        ("arguments".to_string(), Value::Object(None)),
+        (PROTO_PROP.to_string(), Arc::clone(object_prototype).into()),
+    ]));
 
-        Arc::new(Mutex::new(func_proto))
-    };
+    Arc::new(Mutex::new(func_proto))
 }
 
 pub fn load(interpreter: &Interpreter) {
     // Function
     let func = Object::new_empty(HashMap::from([(
         "prototype".to_string(),
-        Arc::clone(&PROTOTYPE).into(),
+        Arc::clone(&interpreter.state.realm.function_prototype).into(),
     )]));
 
     interpreter
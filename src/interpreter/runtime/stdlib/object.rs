@@ -9,24 +9,21 @@ use crate::{
     Interpreter,
 };
 
-use lazy_static::lazy_static;
-
-lazy_static! {
-    pub static ref PROTOTYPE: ObjectRef = {
-        let obj = Object::new_empty(HashMap::from([(
-            PROTO_PROP.to_string(),
-            Value::Object(None),
-        )]));
+/// Creates `Object.prototype`
+pub fn prototype() -> ObjectRef {
+    let obj = Object::new_empty(HashMap::from([(
+        PROTO_PROP.to_string(),
+        Value::Object(None),
+    )]));
 
-        Arc::new(Mutex::new(obj))
-    };
+    Arc::new(Mutex::new(obj))
 }
 
 pub fn load(interpreter: &Interpreter) {
     // Object
     let obj = Object::new_empty(HashMap::from([(
         "prototype".to_string(),
-        Arc::clone(&PROTOTYPE).into(),
+        Arc::clone(&interpreter.state.realm.object_prototype).into(),
     )]));
 
     interpreter
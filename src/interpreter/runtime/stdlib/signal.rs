@@ -0,0 +1,66 @@
+//! Signals made by `use`, which are values that trigger `when` watchers when they are set
+
+use std::{
+    borrow::Cow,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    prelude::Wrapper,
+    runtime::{state::FunctionVariant, value::Value, Error},
+    Interpreter,
+};
+
+#[derive(Debug)]
+pub struct Signal(Mutex<Value>);
+
+impl Signal {
+    pub fn get(&self) -> Value {
+        self.0.lock().unwrap().clone()
+    }
+
+    pub fn set(&self, value: Value) {
+        *self.0.lock().unwrap() = value;
+    }
+}
+
+/// What calling a function of a signal does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignalAccess {
+    Get,
+    Set,
+    /// Reads the value when called without an argument, and sets it when called with one
+    GetOrSet,
+}
+
+/// Makes a signal with the initial value
+/// # Returns
+/// - A function that reads the signal with no argument and sets it with one, like `score()!` and `score(9)!`
+/// - It can also be destructured into the getter and the setter, like `const var [getScore, setScore] = use 0!`
+pub fn use_signal(
+    interpreter: &Interpreter,
+    args: Vec<Wrapper<Cow<Value>>>,
+) -> Result<Value, Error> {
+    let initial = args
+        .first()
+        .map(|value| value.as_ref().clone())
+        .unwrap_or(Value::Undefined);
+    let signal = Arc::new(Signal(Mutex::new(initial)));
+
+    let state = &interpreter.state;
+    let func = |access| FunctionVariant::Signal {
+        signal: Arc::clone(&signal),
+        access,
+    };
+    let getter = state.add_func(func(SignalAccess::Get), Some(0));
+    let setter = state.add_func(func(SignalAccess::Set), Some(1));
+    let combined = state.add_func(func(SignalAccess::GetOrSet), Some(1));
+
+    // destructuring starts from index -1 like arrays do
+    let mut obj = combined.lock().unwrap();
+    obj.set_property("-1", getter.into());
+    obj.set_property("0", setter.into());
+    drop(obj);
+
+    Ok(combined.into())
+}
@@ -1,4 +1,4 @@
-use std::{borrow::Cow, io::Write};
+use std::borrow::Cow;
 
 use crate::{
     interpreter::runtime::{error::Error, value::Value},
@@ -6,29 +6,26 @@ use crate::{
     Interpreter,
 };
 
-pub fn print(_interpreter: &Interpreter, args: Vec<Wrapper<Cow<Value>>>) -> Result<Value, Error> {
-    let mut stdout = std::io::stdout().lock();
+pub fn print(interpreter: &Interpreter, args: Vec<Wrapper<Cow<Value>>>) -> Result<Value, Error> {
+    let mut io = interpreter.state.io.lock().unwrap();
     for arg in args {
-        writeln!(stdout, "{}", arg.as_ref())
+        io.write(&format!("{}\n", arg.as_ref()))
             .map_err(|err| Error::RuntimeException(err.to_string()))?;
     }
 
     Ok(Value::Undefined)
 }
 
-pub fn input(_interpreter: &Interpreter, args: Vec<Wrapper<Cow<Value>>>) -> Result<Value, Error> {
-    let mut stdout = std::io::stdout();
-    let stdin = std::io::stdin();
+pub fn input(interpreter: &Interpreter, args: Vec<Wrapper<Cow<Value>>>) -> Result<Value, Error> {
+    let mut io = interpreter.state.io.lock().unwrap();
     for arg in args {
-        write!(stdout, "{}", arg.as_ref())
+        io.write(&arg.as_ref().to_string())
             .map_err(|err| Error::RuntimeException(err.to_string()))?;
     }
-    stdout
-        .flush()
+    io.flush()
         .map_err(|err| Error::RuntimeException(err.to_string()))?;
-    let mut input = String::new();
-    stdin
-        .read_line(&mut input)
+    let input = io
+        .read_line()
         .map_err(|err| Error::RuntimeException(err.to_string()))?;
     Ok(Value::String(input.trim().to_string()))
 }
@@ -0,0 +1,40 @@
+use std::{borrow::Cow, sync::Arc};
+
+use crate::{
+    interpreter::evaluators::variable::VarType,
+    prelude::Wrapper,
+    runtime::{
+        state::FunctionVariant,
+        value::{Symbol, Value},
+        Error,
+    },
+    Interpreter,
+};
+
+/// Creates a symbol that is different from every other symbol, even ones with the same description
+pub fn constructor(
+    _interpreter: &Interpreter,
+    args: Vec<Wrapper<Cow<Value>>>,
+) -> Result<Value, Error> {
+    let description = args.first().and_then(|arg| match arg.as_ref() {
+        Value::Undefined => None,
+        description => Some(description.to_string()),
+    });
+
+    Ok(Value::Symbol(Symbol::new(description)))
+}
+
+pub fn load(interpreter: &Interpreter) {
+    let state = &interpreter.state;
+    // the description is optional
+    let symbol = state.add_func(FunctionVariant::Native(Arc::new(constructor)), None);
+
+    // well-known symbols
+    {
+        let mut symbol = symbol.lock().unwrap();
+        symbol.set_property("toPrimitive", Value::Symbol(Symbol::to_primitive()));
+        symbol.set_property("iterator", Value::Symbol(Symbol::iterator()));
+    }
+
+    state.add_var("Symbol", symbol.into(), 0, VarType::VarVar, None);
+}
@@ -1,19 +1,31 @@
-use std::{borrow::Cow, thread, time::Duration};
+use std::{borrow::Cow, time::Duration};
 
 use crate::{
+    parsers::PosWithInfo,
     prelude::Wrapper,
     runtime::{value::Value, Error},
     Interpreter,
 };
 
-pub fn sleep(_interpreter: &Interpreter, args: Vec<Wrapper<Cow<Value>>>) -> Result<Value, Error> {
+/// Waits on the clock of the interpreter, while variables whose lifetime is up keep expiring
+pub fn sleep(
+    eval_args: PosWithInfo,
+    _this: Value,
+    args: Vec<Wrapper<Cow<Value>>>,
+) -> Result<Value, Error> {
     let Some(ms) = args.first() else {
         return Ok(Value::Undefined);
     };
 
     let ms = f64::try_from(ms.0.as_ref())? as u64;
 
-    thread::sleep(Duration::from_millis(ms));
+    let state = &eval_args.extra.0.state;
+    state.sleep(Duration::from_millis(ms), eval_args)?;
 
     Ok(Value::Undefined)
 }
+
+/// Does nothing, which gives the other async function calls a turn
+pub fn noop(_interpreter: &Interpreter, _args: Vec<Wrapper<Cow<Value>>>) -> Result<Value, Error> {
+    Ok(Value::Undefined)
+}
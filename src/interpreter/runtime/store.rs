@@ -0,0 +1,226 @@
+//! Storage of `const const const` values, which are constant across every interpreter sharing the store
+//!
+//! By default the values are kept in files in the user's data directory, see [`FileStore::user`], so they are shared between processes.
+//! Anything that implements [`Store`] can be installed instead with [`Interpreter::with_store`](crate::Interpreter::with_store) or
+//! [`Interpreter::set_store`](crate::Interpreter::set_store), like a [`MemoryStore`] to keep the values to the interpreter
+//!
+//! Values that other interpreters declare are seen from the next `eval`, and regular variables shadow them
+
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+    fmt::Debug,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use num_bigint::BigInt;
+
+use super::value::Value;
+
+/// Where `const const const` values are declared and read from
+/// - Only primitives are stored, since objects can't be shared between interpreters
+pub trait Store: Send {
+    /// Gets the value declared with the name
+    fn get(&mut self, name: &str) -> io::Result<Option<Value>>;
+
+    /// Declares the name with the value, unless it's already declared
+    /// # Returns
+    /// - `false` if the name is already declared, which keeps the stored value as is
+    fn declare(&mut self, name: &str, value: &Value) -> io::Result<bool>;
+}
+
+/// Stores each value in its own file in a directory, which every process using the directory shares
+/// - Declaring is atomic, so only one process can declare a name
+/// - Anyone who can write to the directory can declare values, so it shouldn't be a directory other users can write to
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    dir: PathBuf,
+}
+
+impl FileStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// A directory in the user's data directory, which only the user can write to
+    /// - `$XDG_DATA_HOME` or `~/.local/share` on unix, `~/Library/Application Support` on macOS, and `%APPDATA%` on windows
+    /// - `None` if the data directory can't be found
+    pub fn user() -> Option<Self> {
+        let data_dir = if cfg!(windows) {
+            env::var_os("APPDATA").map(PathBuf::from)
+        } else if cfg!(target_os = "macos") {
+            env::var_os("HOME").map(|home| Path::new(&home).join("Library/Application Support"))
+        } else {
+            env::var_os("XDG_DATA_HOME")
+                .map(PathBuf::from)
+                .filter(|dir| dir.is_absolute())
+                .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))
+        }?;
+
+        Some(Self::new(data_dir.join("dreamberd-const-const-const")))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Names are hex encoded, since they can have any character
+    fn path(&self, name: &str) -> PathBuf {
+        let name = name.bytes().map(|b| format!("{b:02x}")).collect::<String>();
+        self.dir.join(name)
+    }
+}
+
+impl Store for FileStore {
+    fn get(&mut self, name: &str) -> io::Result<Option<Value>> {
+        match fs::read_to_string(self.path(name)) {
+            Ok(contents) => decode(&contents).map(Some).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid value of {name}"),
+                )
+            }),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn declare(&mut self, name: &str, value: &Value) -> io::Result<bool> {
+        static TEMP_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+        let contents = encode(value).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "only primitives can be stored")
+        })?;
+        fs::create_dir_all(&self.dir)?;
+
+        // the value is written to another file first, and linked to the name at once so it's never read half written
+        let temp = self.dir.join(format!(
+            ".{}-{}",
+            std::process::id(),
+            TEMP_COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::write(&temp, contents)?;
+        let linked = fs::hard_link(&temp, self.path(name));
+        fs::remove_file(&temp)?;
+
+        match linked {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// In-memory store
+/// - Cloning this shares the same values, so interpreters in the same process can share it
+#[derive(Debug, Default, Clone)]
+pub struct MemoryStore(Arc<Mutex<HashMap<String, Value>>>);
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Store for MemoryStore {
+    fn get(&mut self, name: &str) -> io::Result<Option<Value>> {
+        Ok(self.0.lock().unwrap().get(name).cloned())
+    }
+
+    fn declare(&mut self, name: &str, value: &Value) -> io::Result<bool> {
+        let mut values = self.0.lock().unwrap();
+        if values.contains_key(name) {
+            return Ok(false);
+        }
+        values.insert(name.to_string(), value.clone());
+        Ok(true)
+    }
+}
+
+/// Writes a primitive as text, `None` for symbols and objects
+fn encode(value: &Value) -> Option<String> {
+    let encoded = match value {
+        Value::Undefined => "undefined".to_string(),
+        Value::Object(None) => "null".to_string(),
+        Value::Boolean(value) => value.to_string(),
+        Value::Number(value) => format!("number {value}"),
+        Value::BigInt(value) => format!("bigint {value}"),
+        Value::String(value) => format!("string {value}"),
+        Value::Symbol(_) | Value::Object(Some(_)) => return None,
+    };
+    Some(encoded)
+}
+
+fn decode(encoded: &str) -> Option<Value> {
+    let value = match encoded.split_once(' ') {
+        Some(("number", value)) => Value::Number(value.parse().ok()?),
+        Some(("bigint", value)) => Value::BigInt(value.parse::<BigInt>().ok()?),
+        Some(("string", value)) => Value::String(value.to_string()),
+        _ => match encoded {
+            "undefined" => Value::Undefined,
+            "null" => Value::Object(None),
+            "true" => Value::Boolean(true),
+            "false" => Value::Boolean(false),
+            _ => return None,
+        },
+    };
+    Some(value)
+}
+
+/// The [`Store`] that is installed on an interpreter
+/// - Most names that are looked up aren't `const const const`, so names that weren't found are remembered until [`StoreHost::refresh`]
+pub(crate) struct StoreHost {
+    store: Box<dyn Store>,
+    missing: HashSet<String>,
+}
+
+impl StoreHost {
+    pub fn new(store: Box<dyn Store>) -> Self {
+        Self {
+            store,
+            missing: HashSet::new(),
+        }
+    }
+
+    pub fn get(&mut self, name: &str) -> io::Result<Option<Value>> {
+        if self.missing.contains(name) {
+            return Ok(None);
+        }
+        let value = self.store.get(name)?;
+        if value.is_none() {
+            self.missing.insert(name.to_string());
+        }
+        Ok(value)
+    }
+
+    pub fn declare(&mut self, name: &str, value: &Value) -> io::Result<bool> {
+        self.missing.remove(name);
+        self.store.declare(name, value)
+    }
+
+    /// Forgets the names that weren't found, so values other interpreters declared since then can be found
+    pub fn refresh(&mut self) {
+        self.missing.clear();
+    }
+}
+
+impl Default for StoreHost {
+    /// Files in the user's data directory, or memory if there is none
+    fn default() -> Self {
+        match FileStore::user() {
+            Some(store) => Self::new(Box::new(store)),
+            None => Self::new(Box::new(MemoryStore::default())),
+        }
+    }
+}
+
+impl Debug for StoreHost {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StoreHost").finish_non_exhaustive()
+    }
+}
@@ -6,7 +6,7 @@ use crate::{
     prelude::Wrapper,
 };
 use num_bigint::BigInt;
-use num_traits::FromPrimitive;
+use num_traits::{FromPrimitive, ToPrimitive, Zero};
 
 use nom::{
     branch::*, bytes::complete::*, character::complete::*, combinator::*, multi::*,
@@ -16,11 +16,18 @@ use nom::{
 mod bigint;
 mod bool;
 mod f64;
+mod history;
+mod key;
 pub mod object;
+mod primitive;
+pub mod string;
 mod symbol;
 
+pub use history::*;
+pub use key::*;
 pub use object::*;
-use symbol::*;
+pub use primitive::*;
+pub use symbol::*;
 
 #[derive(Debug, Clone)]
 /// A value that corresponds to a ECMAScript value
@@ -28,7 +35,7 @@ pub enum Value {
     Number(f64),
     Boolean(bool),
     BigInt(BigInt),
-    /// utf-16 string
+    /// Stored as utf-8, but `length` and indexing count utf-16 code units, see [`string`]
     String(String),
     Undefined,
     Symbol(Symbol),
@@ -38,11 +45,53 @@ pub enum Value {
     Object(Option<ObjectRef>),
 }
 
+/// Most bits a BigInt from `^` can have, since the result can be too big to compute in any reasonable time
+const MAX_BIGINT_POW_BITS: u64 = 1 << 24;
+
 impl Value {
     pub fn pow(&self, rhs: &Self) -> Result<Self, runtime::Error> {
-        Ok(Value::Number(
-            f64::try_from(self)?.powf(f64::try_from(rhs)?),
-        ))
+        let value = match Numeric::new(self.clone(), rhs.clone())? {
+            Numeric::Number(left, right) => Value::Number(left.powf(right)),
+            Numeric::BigInt(left, right) => {
+                let right = right.to_u32().ok_or_else(|| {
+                    runtime::Error::Range("Exponent must be a small positive BigInt".to_string())
+                })?;
+                // the result has at least this many bits, `0n`, `1n` and `-1n` stay as small as they are
+                let min_bits = left.bits().saturating_sub(1) * right as u64;
+                if min_bits > MAX_BIGINT_POW_BITS {
+                    return Err(runtime::Error::Range(
+                        "Maximum BigInt size exceeded".to_string(),
+                    ));
+                }
+                Value::BigInt(left.pow(right))
+            }
+        };
+
+        Ok(value)
+    }
+
+    /// Converts a primitive to a string, like concatenating it with a string does
+    pub fn coerce_string(&self) -> Result<String, runtime::Error> {
+        let string = match self {
+            Value::Number(value) if value.is_infinite() => {
+                if value.is_sign_positive() {
+                    "Infinity".to_string()
+                } else {
+                    "-Infinity".to_string()
+                }
+            }
+            // `-0` is `"0"`
+            Value::Number(value) if *value == 0.0 => "0".to_string(),
+            Value::BigInt(value) => value.to_string(),
+            Value::Symbol(_) => {
+                return Err(runtime::Error::Type(
+                    "Cannot convert a Symbol value to a string".to_string(),
+                ))
+            }
+            value => value.to_string(),
+        };
+
+        Ok(string)
     }
 
     fn same_type(&self, other: &Self) -> bool {
@@ -150,17 +199,11 @@ impl Value {
         }
 
         let start_quote = take_while1(|c| c == '\'' || c == '"');
-        let escape_quote = char('\'');
-        let escape_double_quote = char('"');
-        let new_line = char('n');
-        let escape_char = value(
-            2,
-            tuple((
-                char('\\'),
-                alt((escape_quote, escape_double_quote, new_line)),
-            )),
-        );
-        let string_take_check = value(1, verify(take(1usize), |s: &str| s != "'" && s != "\""));
+        // any char can be escaped, including quotes and another `\`
+        let escape_char = tuple((char('\\'), anychar)).map(|(_, ch)| 1 + ch.len_utf8());
+        // counts bytes, since the string is sliced with it
+        let string_take_check = verify(take(1usize), |s: &str| s != "'" && s != "\"")
+            .map(|s: Position<_, _>| s.input.len());
         let string_inner = alt((escape_char, string_take_check));
         let string_inner = fold_many0(string_inner, || 0, |acc, count| acc + count);
         let (s_new, (start_quotes, string_inner)) = tuple((start_quote, string_inner))(input)?;
@@ -192,20 +235,8 @@ impl Value {
             .zip(chunk.input.chars())
             .all(|(a, b)| a == b)
         {
-            // convert escape chars
-            let from_to = [
-                ("\\n", "\n"),
-                ("\\\\", "\\"),
-                ("\\\'", "\'"),
-                ("\\\"", "\""),
-            ];
-            let mut string_inner = string_inner.to_string();
-            for (from, to) in from_to.iter() {
-                string_inner = string_inner.replace(from, to);
-            }
-
             let (input, _) = take(start_quotes_len)(s_new)?;
-            return Ok((input, Value::String(string_inner)));
+            return Ok((input, Value::String(string::decode_escapes(string_inner))));
         }
 
         Err(nom::Err::Error(nom::error::Error::new(
@@ -217,7 +248,7 @@ impl Value {
 
 impl<'a> Wrapper<Cow<'a, Value>> {
     pub fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        // TODO object to primitive, [@@toPrimitive]() with "number", valueOf(), toString()
+        // objects are converted before this with `Value::to_primitive`, since it can call their methods
         let left = if self.is_primitive() {
             self
         } else {
@@ -270,18 +301,17 @@ impl<'a> Wrapper<Cow<'a, Value>> {
             return Ok(self.loose_eq_primitive_eq_type(other));
         }
 
-        // convert object to primitive
+        // objects compared to primitives are converted before this with `Value::to_primitive`,
+        // what's left are objects compared to null or undefined
         let left = if self.is_primitive() {
             self
         } else {
-            // TODO not finished implementing obj to primitive
             return Ok(false);
         };
 
         let other = if other.is_primitive() {
             other
         } else {
-            // TODO not finished implementing obj to primitive
             return Ok(false);
         };
 
@@ -331,7 +361,12 @@ impl<'a> Wrapper<Cow<'a, Value>> {
                 Ok(left) => left == *other,
                 Err(_) => false,
             },
-            _ => unreachable!(),
+            // same as above with the sides swapped
+            (Value::String(_), Value::Number(_))
+            | (Value::BigInt(_), Value::Number(_))
+            | (Value::BigInt(_), Value::String(_)) => return other.loose_eq(left),
+            // undefined is only loosely equal to null
+            _ => false,
         };
 
         Ok(result)
@@ -377,14 +412,41 @@ impl<'a> Not for Wrapper<Cow<'a, Value>> {
     }
 }
 
+/// Operands of an arithmetic operator, which can't mix Numbers and BigInts
+enum Numeric {
+    Number(f64, f64),
+    BigInt(BigInt, BigInt),
+}
+
+impl Numeric {
+    fn new(left: Value, right: Value) -> Result<Self, runtime::Error> {
+        match (left, right) {
+            (Value::BigInt(left), Value::BigInt(right)) => Ok(Numeric::BigInt(left, right)),
+            (Value::BigInt(_), _) | (_, Value::BigInt(_)) => Err(runtime::Error::Type(
+                "Cannot mix BigInt and other types, use explicit conversions".to_string(),
+            )),
+            (left, right) => Ok(Numeric::Number(f64::try_from(left)?, f64::try_from(right)?)),
+        }
+    }
+}
+
 impl Add for Value {
     type Output = Result<Self, runtime::Error>;
 
+    /// Concatenates if either side is a string, otherwise adds numbers
     fn add(self, rhs: Self) -> Self::Output {
-        let left = f64::try_from(self)?;
-        let right = f64::try_from(rhs)?;
+        if matches!(self, Value::String(_)) || matches!(rhs, Value::String(_)) {
+            let left = self.coerce_string()?;
+            let right = rhs.coerce_string()?;
+            return Ok(Value::String(left + &right));
+        }
 
-        Ok(Value::Number(left + right))
+        let value = match Numeric::new(self, rhs)? {
+            Numeric::Number(left, right) => Value::Number(left + right),
+            Numeric::BigInt(left, right) => Value::BigInt(left + right),
+        };
+
+        Ok(value)
     }
 }
 
@@ -392,10 +454,8 @@ impl<'a> Add for Wrapper<Cow<'a, Value>> {
     type Output = Result<Self, runtime::Error>;
 
     fn add(self, rhs: Self) -> Self::Output {
-        let left = f64::try_from(self)?;
-        let right = f64::try_from(rhs)?;
-
-        Ok(Wrapper(Cow::Owned(Value::Number(left + right))))
+        let value = (self.0.into_owned() + rhs.0.into_owned())?;
+        Ok(Wrapper(Cow::Owned(value)))
     }
 }
 
@@ -403,10 +463,12 @@ impl Sub for Value {
     type Output = Result<Self, runtime::Error>;
 
     fn sub(self, rhs: Self) -> Self::Output {
-        let left = f64::try_from(self)?;
-        let right = f64::try_from(rhs)?;
+        let value = match Numeric::new(self, rhs)? {
+            Numeric::Number(left, right) => Value::Number(left - right),
+            Numeric::BigInt(left, right) => Value::BigInt(left - right),
+        };
 
-        Ok(Value::Number(left - right))
+        Ok(value)
     }
 }
 
@@ -414,10 +476,8 @@ impl<'a> Sub for Wrapper<Cow<'a, Value>> {
     type Output = Result<Self, runtime::Error>;
 
     fn sub(self, rhs: Self) -> Self::Output {
-        let left = f64::try_from(self)?;
-        let right = f64::try_from(rhs)?;
-
-        Ok(Wrapper(Cow::Owned(Value::Number(left - right))))
+        let value = (self.0.into_owned() - rhs.0.into_owned())?;
+        Ok(Wrapper(Cow::Owned(value)))
     }
 }
 
@@ -425,10 +485,12 @@ impl Mul for Value {
     type Output = Result<Self, runtime::Error>;
 
     fn mul(self, rhs: Self) -> Self::Output {
-        let left = f64::try_from(self)?;
-        let right = f64::try_from(rhs)?;
+        let value = match Numeric::new(self, rhs)? {
+            Numeric::Number(left, right) => Value::Number(left * right),
+            Numeric::BigInt(left, right) => Value::BigInt(left * right),
+        };
 
-        Ok(Value::Number(left * right))
+        Ok(value)
     }
 }
 
@@ -436,27 +498,27 @@ impl<'a> Mul for Wrapper<Cow<'a, Value>> {
     type Output = Result<Self, runtime::Error>;
 
     fn mul(self, rhs: Self) -> Self::Output {
-        let left = f64::try_from(self)?;
-        let right = f64::try_from(rhs)?;
-
-        Ok(Wrapper(Cow::Owned(Value::Number(left * right))))
+        let value = (self.0.into_owned() * rhs.0.into_owned())?;
+        Ok(Wrapper(Cow::Owned(value)))
     }
 }
 
 impl Div for Value {
     type Output = Result<Self, runtime::Error>;
 
+    /// Dividing a number by zero is undefined, while a BigInt can't be divided by zero
     fn div(self, rhs: Self) -> Self::Output {
-        let left = f64::try_from(self)?;
-        let right = f64::try_from(rhs)?;
-
-        let res = if right == 0.0 {
-            Value::Undefined
-        } else {
-            Value::Number(left / right)
+        let value = match Numeric::new(self, rhs)? {
+            Numeric::Number(_, 0.0) => Value::Undefined,
+            Numeric::Number(left, right) => Value::Number(left / right),
+            Numeric::BigInt(_, right) if right.is_zero() => {
+                return Err(runtime::Error::Range("Division by zero".to_string()))
+            }
+            // rounds towards zero
+            Numeric::BigInt(left, right) => Value::BigInt(left / right),
         };
 
-        Ok(res)
+        Ok(value)
     }
 }
 
@@ -464,16 +526,8 @@ impl<'a> Div for Wrapper<Cow<'a, Value>> {
     type Output = Result<Self, runtime::Error>;
 
     fn div(self, rhs: Self) -> Self::Output {
-        let left = f64::try_from(self)?;
-        let right = f64::try_from(rhs)?;
-
-        let res = if right == 0.0 {
-            Value::Undefined
-        } else {
-            Value::Number(left / right)
-        };
-
-        Ok(Wrapper(Cow::Owned(res)))
+        let value = (self.0.into_owned() / rhs.0.into_owned())?;
+        Ok(Wrapper(Cow::Owned(value)))
     }
 }
 
@@ -481,10 +535,15 @@ impl Rem for Value {
     type Output = Result<Self, runtime::Error>;
 
     fn rem(self, rhs: Self) -> Self::Output {
-        let left = f64::try_from(self)?;
-        let right = f64::try_from(rhs)?;
+        let value = match Numeric::new(self, rhs)? {
+            Numeric::Number(left, right) => Value::Number(left % right),
+            Numeric::BigInt(_, right) if right.is_zero() => {
+                return Err(runtime::Error::Range("Division by zero".to_string()))
+            }
+            Numeric::BigInt(left, right) => Value::BigInt(left % right),
+        };
 
-        Ok(Value::Number(left % right))
+        Ok(value)
     }
 }
 
@@ -492,10 +551,8 @@ impl<'a> Rem for Wrapper<Cow<'a, Value>> {
     type Output = Result<Self, runtime::Error>;
 
     fn rem(self, rhs: Self) -> Self::Output {
-        let left = f64::try_from(self)?;
-        let right = f64::try_from(rhs)?;
-
-        Ok(Wrapper(Cow::Owned(Value::Number(left % right))))
+        let value = (self.0.into_owned() % rhs.0.into_owned())?;
+        Ok(Wrapper(Cow::Owned(value)))
     }
 }
 
@@ -503,8 +560,11 @@ impl<'a> Neg for Wrapper<Cow<'a, Value>> {
     type Output = Result<Self, runtime::Error>;
 
     fn neg(self) -> Self::Output {
-        let value = f64::try_from(self)?;
+        let value = match self.0.as_ref() {
+            Value::BigInt(value) => Value::BigInt(-value),
+            value => Value::Number(-f64::try_from(value)?),
+        };
 
-        Ok(Wrapper(Cow::Owned(Value::Number(-value))))
+        Ok(Wrapper(Cow::Owned(value)))
     }
 }
@@ -41,9 +41,10 @@ impl<'a> TryFrom<Wrapper<Cow<'a, Value>>> for f64 {
             }
             Value::String(value) => value.parse().unwrap_or(f64::NAN),
             Value::Object(value) => match value {
+                // converting needs the interpreter to call methods, see `Value::to_primitive`
                 Some(_) => {
                     return Err(runtime::Error::Type(
-                        "Not implemented object to number coercion".to_string(), // TODO this isn't right
+                        "Cannot convert object to number".to_string(),
                     ));
                 }
                 None => 0.0,
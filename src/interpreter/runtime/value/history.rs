@@ -0,0 +1,37 @@
+use std::collections::VecDeque;
+
+use super::Value;
+
+/// Number of past values kept for each variable and property by default
+pub const DEFAULT_HISTORY_LEN: usize = 8;
+
+#[derive(Debug, Clone, Default)]
+/// Past values of a variable or property, the most recent one first
+pub struct History(VecDeque<Value>);
+
+impl History {
+    /// Records the value that is being replaced, forgetting values older than `len` assignments
+    pub fn push(&mut self, value: Value, len: usize) {
+        self.0.push_front(value);
+        self.0.truncate(len);
+    }
+
+    /// Gets the value from `steps` assignments ago
+    /// - `undefined` if it's older than what's kept, or if there were not that many assignments
+    pub fn get(&self, steps: usize) -> Value {
+        steps
+            .checked_sub(1)
+            .and_then(|i| self.0.get(i))
+            .cloned()
+            .unwrap_or(Value::Undefined)
+    }
+
+    /// Every value that is kept
+    pub fn values(&self) -> impl Iterator<Item = &Value> {
+        self.0.iter()
+    }
+
+    pub fn into_values(self) -> impl Iterator<Item = Value> {
+        self.0.into_iter()
+    }
+}
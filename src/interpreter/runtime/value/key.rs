@@ -0,0 +1,72 @@
+use std::fmt::Display;
+
+use crate::{interpreter::runtime::Error, parsers::PosWithInfo};
+
+use super::{primitive::Hint, Symbol, Value};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// Key of an object property, where a string and a symbol with the same description are different keys
+pub enum PropertyKey {
+    String(String),
+    Symbol(Symbol),
+}
+
+impl PropertyKey {
+    /// Gets the key a value is used as in bracket notation
+    /// - Objects are converted to a primitive first, and anything other than a symbol is converted to a string
+    /// - Numbers convert like they do to strings, so `0.5` is the key `"0.5"`
+    pub fn from_value(value: &Value, args: PosWithInfo) -> Result<Self, Error> {
+        match value.to_primitive(args, Hint::String)? {
+            Value::Symbol(symbol) => Ok(Self::Symbol(symbol)),
+            value => Ok(Self::String(value.coerce_string()?)),
+        }
+    }
+
+    /// Gets the number of an array index key, which is a finite number written the way it converts to a string
+    /// - `"0.5"` is an index between `"0"` and `"1"`, but `"02"` isn't an index
+    pub fn index(&self) -> Option<f64> {
+        let Self::String(key) = self else {
+            return None;
+        };
+        let index = key.parse::<f64>().ok().filter(|index| index.is_finite())?;
+        let canonical = Value::Number(index).coerce_string().ok()?;
+
+        (canonical == *key).then_some(index)
+    }
+}
+
+impl From<&str> for PropertyKey {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl From<String> for PropertyKey {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<Symbol> for PropertyKey {
+    fn from(value: Symbol) -> Self {
+        Self::Symbol(value)
+    }
+}
+
+impl From<PropertyKey> for Value {
+    fn from(value: PropertyKey) -> Self {
+        match value {
+            PropertyKey::String(str) => Value::String(str),
+            PropertyKey::Symbol(symbol) => Value::Symbol(symbol),
+        }
+    }
+}
+
+impl Display for PropertyKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::String(str) => write!(f, "{str}"),
+            Self::Symbol(symbol) => write!(f, "[{symbol}]"),
+        }
+    }
+}
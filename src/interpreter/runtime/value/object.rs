@@ -1,108 +1,239 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     fmt::Display,
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
 };
 
-use crate::runtime::stdlib::object;
+use crate::runtime::realm::Realm;
 
-use super::Value;
+use super::{History, PropertyKey, Value};
 
 pub const PROTO_PROP: &str = "__proto__";
 
 pub type ObjectRef = Arc<Mutex<Object>>;
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct Object {
-    properties: HashMap<String, Value>,
+    properties: HashMap<PropertyKey, Value>,
+    /// Past values of properties assigned by the code
+    history: HashMap<PropertyKey, History>,
+    /// Number of properties of all objects on the heap, if this one is on it
+    heap_properties: Option<Arc<AtomicUsize>>,
+    /// Properties that aren't printed, which are the built-in methods of prototypes
+    hidden: HashSet<PropertyKey>,
 }
 
 impl Object {
-    /// Creates a new object with the default prototype
-    pub fn new(mut properties: HashMap<String, Value>) -> Self {
+    /// Creates a new object with the default prototype of the realm
+    pub fn new(realm: &Realm, mut properties: HashMap<String, Value>) -> Self {
         if properties.contains_key(PROTO_PROP) {
-            return Self { properties };
+            return Self::new_empty(properties);
         }
 
-        // unwrap shouldn't fail as variables can't be deleted
         // TODO: prototype should be const when that's implemented, or somehow be readonly
         properties.insert(
             PROTO_PROP.to_string(),
-            Arc::clone(&object::PROTOTYPE).into(),
+            Arc::clone(&realm.object_prototype).into(),
         );
 
-        Self { properties }
+        Self::new_empty(properties)
     }
 
     pub fn new_empty(properties: HashMap<String, Value>) -> Self {
-        Self { properties }
+        Self {
+            properties: properties
+                .into_iter()
+                .map(|(key, value)| (key.into(), value))
+                .collect(),
+            history: HashMap::new(),
+            heap_properties: None,
+            hidden: HashSet::new(),
+        }
+    }
+
+    /// Hides every property other than the prototype from being printed, for prototypes of built-in methods
+    pub(crate) fn hide_methods(&mut self) {
+        self.hidden = self
+            .properties
+            .keys()
+            .filter(|key| **key != PROTO_PROP.into())
+            .cloned()
+            .collect();
+    }
+
+    /// Counts the properties towards the heap, which keeps counting them as they are added and removed
+    pub(crate) fn count_properties(&mut self, heap_properties: &Arc<AtomicUsize>) {
+        heap_properties.fetch_add(self.properties.len(), Ordering::Relaxed);
+        self.heap_properties = Some(Arc::clone(heap_properties));
+    }
+
+    /// Takes every value the object refers to, leaving it empty
+    pub(crate) fn take_values(&mut self) -> Vec<Value> {
+        self.count_removed(self.properties.len());
+        let properties = self.properties.drain().map(|(_, value)| value);
+        let history = self
+            .history
+            .drain()
+            .flat_map(|(_, history)| history.into_values());
+        properties.chain(history).collect()
+    }
+
+    /// Every value the object refers to, including past values
+    pub(crate) fn values(&self) -> impl Iterator<Item = &Value> {
+        let history = self.history.values().flat_map(|history| history.values());
+        self.properties.values().chain(history)
+    }
+
+    fn count_added(&self, count: usize) {
+        if let Some(heap_properties) = &self.heap_properties {
+            heap_properties.fetch_add(count, Ordering::Relaxed);
+        }
+    }
+
+    fn count_removed(&self, count: usize) {
+        if let Some(heap_properties) = &self.heap_properties {
+            heap_properties.fetch_sub(count, Ordering::Relaxed);
+        }
     }
 
     pub fn get_property(&self, key: &str) -> Option<Value> {
+        self.get(&key.into())
+    }
+
+    /// Gets the property with a string or symbol key, looking up the prototype chain
+    pub fn get(&self, key: &PropertyKey) -> Option<Value> {
         if let Some(value) = self.properties.get(key) {
             Some(value.to_owned())
-        } else if key == PROTO_PROP {
+        } else if matches!(key, PropertyKey::String(key) if key == PROTO_PROP) {
             None
         } else {
             // prototype chain
-            let Some(value) = self.properties.get(PROTO_PROP) else {
-                return None;
-            };
+            let value = self.properties.get(&PROTO_PROP.into())?;
 
             let Value::Object(Some(value)) = value else {
                 return None;
             };
 
             let obj = value.lock().unwrap();
-            obj.get_property(key)
+            obj.get(key)
         }
     }
 
     pub fn set_property(&mut self, key: &str, value: Value) {
-        self.properties.insert(key.to_string(), value);
+        self.set(key.into(), value);
+    }
+
+    /// Sets the property with a string or symbol key
+    pub fn set(&mut self, key: PropertyKey, value: Value) {
+        if self.properties.insert(key, value).is_none() {
+            self.count_added(1);
+        }
     }
 
-    pub fn array_obj_iter(&self) -> ArrayObjIter {
+    pub fn remove_property(&mut self, key: &PropertyKey) {
+        if self.properties.remove(key).is_some() {
+            self.count_removed(1);
+        }
+        self.history.remove(key);
+    }
+
+    /// Sets the property while keeping up to `history_len` of its past values
+    pub fn set_property_tracked(&mut self, key: PropertyKey, value: Value, history_len: usize) {
+        let old = self.properties.insert(key.clone(), value);
+        if old.is_none() {
+            self.count_added(1);
+        }
+        self.history
+            .entry(key)
+            .or_default()
+            .push(old.unwrap_or(Value::Undefined), history_len);
+    }
+
+    /// Gets the value of the property from `steps` assignments ago
+    pub fn get_previous_property(&self, key: &PropertyKey, steps: usize) -> Value {
+        self.history
+            .get(key)
+            .map(|history| history.get(steps))
+            .unwrap_or(Value::Undefined)
+    }
+
+    /// Keys of the array items sorted by their index, so `0.5` comes between `0` and `1`
+    fn index_keys(&self) -> Vec<(f64, &PropertyKey)> {
+        let mut keys = self
+            .properties
+            .keys()
+            .filter_map(|key| Some((key.index()?, key)))
+            .collect::<Vec<_>>();
+        keys.sort_by(|(left, _), (right, _)| left.total_cmp(right));
+        keys
+    }
+
+    /// Keys of the array items in order
+    pub fn array_keys(&self) -> Vec<PropertyKey> {
+        self.index_keys()
+            .into_iter()
+            .map(|(_, key)| key.clone())
+            .collect()
+    }
+
+    /// Iterates over the array items in the order of their index, which can be fractional
+    pub fn array_obj_iter(&self) -> ArrayObjIter<'_> {
+        let items = self
+            .index_keys()
+            .into_iter()
+            .map(|(_, key)| &self.properties[key])
+            .collect::<Vec<_>>();
         ArrayObjIter {
-            obj: self,
-            index: 0,
+            items: items.into_iter(),
         }
     }
 }
 
 #[derive(Debug)]
 pub struct ArrayObjIter<'a> {
-    obj: &'a Object,
-    index: usize,
+    items: std::vec::IntoIter<&'a Value>,
 }
 
 impl Iterator for ArrayObjIter<'_> {
     type Item = Value;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let index = if self.index == 0 {
-            "-1".to_string()
-        } else {
-            (self.index - 1).to_string()
-        };
-
-        let prop = self.obj.get_property(&index);
-
-        self.index += 1;
+        self.items.next().cloned()
+    }
+}
 
-        prop
+impl Drop for Object {
+    fn drop(&mut self) {
+        self.count_removed(self.properties.len());
     }
 }
 
 impl Display for Object {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if self.properties.is_empty() {
+        if self.properties.keys().all(|key| self.hidden.contains(key)) {
             return write!(f, "{{}}");
         }
 
-        let properties = self
+        // array items go first in the order of their index, then the rest by name
+        let mut properties = self
+            .index_keys()
+            .into_iter()
+            .filter(|(_, key)| !self.hidden.contains(*key))
+            .map(|(_, key)| (key, &self.properties[key]))
+            .collect::<Vec<_>>();
+        let mut rest = self
             .properties
             .iter()
+            .filter(|(key, _)| key.index().is_none() && !self.hidden.contains(*key))
+            .collect::<Vec<_>>();
+        rest.sort_by_key(|(key, _)| key.to_string());
+        properties.extend(rest);
+
+        let properties = properties
+            .into_iter()
             .map(|(key, value)| {
                 let value = if let Value::String(value) = value {
                     let value = value.replace('\n', "\\n");
@@ -0,0 +1,88 @@
+use std::{borrow::Cow, fmt::Display};
+
+use crate::{
+    interpreter::runtime::{self, error::StackFrame},
+    parsers::PosWithInfo,
+    prelude::Wrapper,
+};
+
+use super::{PropertyKey, Symbol, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Which primitive is preferred when converting an object, which is passed to `[Symbol.toPrimitive]`
+pub enum Hint {
+    Default,
+    Number,
+    String,
+}
+
+impl Display for Hint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let hint = match self {
+            Hint::Default => "default",
+            Hint::Number => "number",
+            Hint::String => "string",
+        };
+        write!(f, "{hint}")
+    }
+}
+
+impl Value {
+    /// Converts an object to a primitive, other values are returned as is
+    /// - `[Symbol.toPrimitive]` is called with the hint if the object has it
+    /// - Otherwise `valueOf` and `toString` are tried, in the order of the hint
+    /// - An object without `toString` is `"[object Object]"`, like it inherited it from `Object.prototype`
+    pub fn to_primitive(&self, args: PosWithInfo, hint: Hint) -> Result<Value, runtime::Error> {
+        let Value::Object(Some(obj)) = self else {
+            return Ok(self.clone());
+        };
+        let state = &args.extra.0.state;
+        // the object isn't locked while calling, since the method can use `this`
+        let get = |key: PropertyKey| obj.lock().unwrap().get(&key);
+        let call = |name: &str, func: &Value, call_args| {
+            let frame = StackFrame {
+                name: name.to_string(),
+                call_site: args.into(),
+            };
+            state.call_value(args, frame, func, self.clone(), call_args)
+        };
+
+        let exotic = get(Symbol::to_primitive().into())
+            .filter(|func| !matches!(func, Value::Undefined | Value::Object(None)));
+        if let Some(exotic) = exotic {
+            let hint = Wrapper(Cow::Owned(Value::String(hint.to_string())));
+            let value = call("[Symbol.toPrimitive]", &exotic, vec![hint]).ok_or_else(|| {
+                runtime::Error::Type("Symbol.toPrimitive is not a function".to_string())
+            })??;
+            if matches!(value, Value::Object(Some(_))) {
+                return Err(runtime::Error::Type(
+                    "Cannot convert object to primitive value".to_string(),
+                ));
+            }
+            return Ok(value);
+        }
+
+        let methods = match hint {
+            Hint::String => ["toString", "valueOf"],
+            Hint::Default | Hint::Number => ["valueOf", "toString"],
+        };
+        for name in methods {
+            let Some(method) = get(name.into()) else {
+                if name == "toString" {
+                    return Ok(Value::String("[object Object]".to_string()));
+                }
+                continue;
+            };
+            if let Some(value) = call(name, &method, Vec::new()) {
+                let value = value?;
+                if !matches!(value, Value::Object(Some(_))) {
+                    return Ok(value);
+                }
+            }
+        }
+
+        Err(runtime::Error::Type(
+            "Cannot convert object to primitive value".to_string(),
+        ))
+    }
+}
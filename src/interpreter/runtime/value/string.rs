@@ -0,0 +1,145 @@
+//! Escape sequences of string literals, and properties of strings
+//! - Strings are stored as utf-8, but `length` and indexing count utf-16 code units like ECMAScript does
+
+use super::{PropertyKey, Value};
+
+/// Decodes the escape sequence right after a `\`
+/// - Unknown escapes are the char itself, like `\q` is `q`
+/// # Returns
+/// - The decoded char, `None` for a line continuation
+/// - Number of bytes of `input` that were used
+pub fn decode_escape(input: &str) -> (Option<char>, usize) {
+    let Some(ch) = input.chars().next() else {
+        return (Some('\\'), 0);
+    };
+
+    let decoded = match ch {
+        'n' => '\n',
+        't' => '\t',
+        'r' => '\r',
+        '0' => '\0',
+        'b' => '\u{8}',
+        'f' => '\u{c}',
+        'v' => '\u{b}',
+        // line continuation
+        '\n' | '\u{2028}' | '\u{2029}' => return (None, ch.len_utf8()),
+        '\r' if input[1..].starts_with('\n') => return (None, 2),
+        '\r' => return (None, 1),
+        'x' => {
+            if let Some(ch) = hex(&input[1..], 2).and_then(char::from_u32) {
+                return (Some(ch), 3);
+            }
+            ch
+        }
+        'u' => {
+            if let Some((ch, len)) = unicode_escape(&input[1..]) {
+                return (Some(ch), len + 1);
+            }
+            ch
+        }
+        ch => ch,
+    };
+
+    (Some(decoded), ch.len_utf8())
+}
+
+/// Decodes every escape sequence in the text between the quotes of a string literal
+pub fn decode_escapes(raw: &str) -> String {
+    let mut decoded = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(index) = rest.find('\\') {
+        decoded.push_str(&rest[..index]);
+        let (ch, len) = decode_escape(&rest[index + 1..]);
+        decoded.extend(ch);
+        rest = &rest[index + 1 + len..];
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Parses exactly `len` hex digits
+fn hex(input: &str, len: usize) -> Option<u32> {
+    let digits = input.get(..len)?;
+    if !digits.chars().all(|ch| ch.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(digits, 16).ok()
+}
+
+/// Decodes what comes after `\u`, which is `{1F600}` or `D83D` that can be followed by `\uDE00`
+/// - Surrogates that aren't part of a pair can't be in a rust string, so they become `U+FFFD`
+fn unicode_escape(input: &str) -> Option<(char, usize)> {
+    if let Some(braced) = input.strip_prefix('{') {
+        let end = braced.find('}')?;
+        let digits = &braced[..end];
+        if digits.is_empty() || digits.len() > 6 || !digits.chars().all(|ch| ch.is_ascii_hexdigit())
+        {
+            return None;
+        }
+        let code = u32::from_str_radix(digits, 16).ok()?;
+        if code > 0x10FFFF {
+            return None;
+        }
+        let ch = char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER);
+        return Some((ch, end + 2));
+    }
+
+    let unit = hex(input, 4)?;
+    if let Some(ch) = char::from_u32(unit) {
+        return Some((ch, 4));
+    }
+
+    // surrogate pair
+    let low = input[4..]
+        .strip_prefix("\\u")
+        .and_then(|low| hex(low, 4))
+        .filter(|low| (0xDC00..0xE000).contains(low));
+    match low {
+        Some(low) if (0xD800..0xDC00).contains(&unit) => {
+            let code = 0x10000 + ((unit - 0xD800) << 10) + (low - 0xDC00);
+            Some((char::from_u32(code)?, 10))
+        }
+        _ => Some((char::REPLACEMENT_CHARACTER, 4)),
+    }
+}
+
+/// Number of utf-16 code units, which is `length` of the string
+pub fn utf16_len(string: &str) -> usize {
+    string.chars().map(char::len_utf16).sum()
+}
+
+/// Gets the utf-16 code unit at the index as a string
+/// - Half of a surrogate pair can't be in a rust string, so it's `U+FFFD`
+pub fn utf16_at(string: &str, index: usize) -> Option<String> {
+    let mut offset = 0;
+    for ch in string.chars() {
+        let len = ch.len_utf16();
+        if index < offset + len {
+            let ch = if len == 1 {
+                ch
+            } else {
+                char::REPLACEMENT_CHARACTER
+            };
+            return Some(ch.to_string());
+        }
+        offset += len;
+    }
+    None
+}
+
+/// Gets a property of a string, which is `length` or a character by its index
+pub fn property(string: &str, key: &PropertyKey) -> Option<Value> {
+    let PropertyKey::String(key) = key else {
+        return None;
+    };
+    if key == "length" {
+        return Some(Value::Number(utf16_len(string) as f64));
+    }
+
+    // only canonical indices, so `"01"` isn't `"1"`
+    let index = key
+        .parse::<usize>()
+        .ok()
+        .filter(|index| index.to_string() == *key)?;
+    utf16_at(string, index).map(Value::String)
+}
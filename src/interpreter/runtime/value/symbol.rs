@@ -1,13 +1,55 @@
-use std::fmt::Display;
+use std::{
+    fmt::Display,
+    hash::{Hash, Hasher},
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
 use super::Value;
 
+/// Well-known symbols, which are the same for every interpreter
+/// - The index is the id of the symbol
+const WELL_KNOWN: &[&str] = &["Symbol.toPrimitive", "Symbol.iterator"];
+
+/// Id of the next symbol, which is unique for the whole process
+static NEXT_ID: AtomicUsize = AtomicUsize::new(WELL_KNOWN.len());
+
 #[derive(Debug, Clone)]
 pub struct Symbol {
     description: Option<String>,
     id: usize,
 }
 
+impl Symbol {
+    /// Creates a symbol that is different from every other symbol
+    pub fn new(description: Option<String>) -> Self {
+        Self {
+            description,
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
+    /// `Symbol.toPrimitive`
+    pub fn to_primitive() -> Self {
+        Self::well_known(0)
+    }
+
+    /// `Symbol.iterator`
+    pub fn iterator() -> Self {
+        Self::well_known(1)
+    }
+
+    fn well_known(id: usize) -> Self {
+        Self {
+            description: Some(WELL_KNOWN[id].to_string()),
+            id,
+        }
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+}
+
 impl Display for Symbol {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -27,6 +69,14 @@ impl PartialEq for Symbol {
     }
 }
 
+impl Eq for Symbol {}
+
+impl Hash for Symbol {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
 impl<'a> From<&'a Value> for &'a Symbol {
     fn from(value: &'a Value) -> Self {
         if let Value::Symbol(value) = value {
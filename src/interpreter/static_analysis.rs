@@ -1,5 +1,6 @@
 //! Responsible for doing static analysis operation on the code before AST creation
 
+pub mod diagnostics;
 mod parsers;
 #[cfg(test)]
 mod tests;
@@ -44,14 +45,29 @@ impl HoistedVarInfo {
         let Statement::VariableDecl(decl) = statement else {
             return None;
         };
-        decl.expression.eval(args).ok().map(|x| x.0.into_owned())
+        match decl.expression.eval(args) {
+            Ok(value) => Some(value.0.into_owned()),
+            Err(_) => {
+                // the error is ignored, so its trace shouldn't be attached to a later one
+                args.extra.0.state.take_error_trace();
+                None
+            }
+        }
     }
 }
 
 impl Analysis {
     /// Does a static analysis of code
     pub fn analyze(input: &str) -> Self {
-        let mut input = Position::new(input);
+        Self::analyze_at(input, 1)
+    }
+
+    /// Does a static analysis of code that starts at the line
+    pub fn analyze_at(input: &str, line: usize) -> Self {
+        let mut input = Position {
+            line,
+            ..Position::new(input)
+        };
         let mut hoisted_vars = Vec::new();
 
         loop {
@@ -0,0 +1,254 @@
+//! Finds problems in the code that would otherwise be parsed into something unexpected
+//! - Because of implicit strings, most malformed code is still valid code, so this is opt-in
+
+use crate::{
+    interpreter::error::{Error, ParseErrorKind},
+    parsers::{is_ws, types::Position, ws},
+    runtime::locale::Locale,
+};
+
+fn is_quote(ch: char) -> bool {
+    ch == '"' || ch == '\''
+}
+
+/// Characters that can be right before a string starts
+fn is_token_boundary(ch: char) -> bool {
+    is_ws(ch) || "!=,:[{".contains(ch)
+}
+
+struct Diagnostics<'a> {
+    code: &'a str,
+    chars: Vec<char>,
+    /// line and column of each char
+    positions: Vec<(usize, usize)>,
+    /// decides how interpolations in strings look
+    locale: &'a Locale,
+}
+
+impl<'a> Diagnostics<'a> {
+    fn new(code: &'a str, locale: &'a Locale) -> Self {
+        let chars = code.chars().collect::<Vec<_>>();
+        let mut positions = Vec::with_capacity(chars.len());
+        let (mut line, mut column) = (1, 1);
+        for ch in chars.iter() {
+            positions.push((line, column));
+            if *ch == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+
+        Self {
+            code,
+            chars,
+            positions,
+            locale,
+        }
+    }
+
+    fn error(&self, kind: ParseErrorKind, index: usize) -> Error {
+        let (line, column) = self.positions[index];
+        let snippet = self.code.lines().nth(line - 1).unwrap_or_default();
+        Error::Parse {
+            kind,
+            line,
+            column,
+            snippet: snippet.to_string(),
+        }
+    }
+
+    fn starts_with(&self, index: usize, s: &str) -> bool {
+        s.chars()
+            .enumerate()
+            .all(|(i, ch)| self.chars.get(index + i) == Some(&ch))
+    }
+
+    fn quote_run(&self, index: usize) -> &[char] {
+        let len = self.chars[index..]
+            .iter()
+            .take_while(|ch| is_quote(**ch))
+            .count();
+        &self.chars[index..index + len]
+    }
+
+    /// Checks the string starting at `index`
+    /// # Returns
+    /// - Index right after the string
+    fn string(&self, index: usize) -> Result<usize, Error> {
+        let opening = self.quote_run(index);
+        let mut i = index + opening.len();
+
+        // `""` and `''''` followed by nothing else is an empty string
+        let (left, right) = opening.split_at(opening.len() / 2);
+        let is_empty_string = opening.len().is_multiple_of(2)
+            && left.iter().rev().zip(right).all(|(l, r)| l == r)
+            && self
+                .chars
+                .get(i)
+                .is_none_or(|ch| is_ws(*ch) || "!,]}".contains(*ch));
+        if is_empty_string {
+            return Ok(i);
+        }
+
+        // find the closing quotes
+        while i < self.chars.len() && !is_quote(self.chars[i]) {
+            if let Some(end) = self.interpolation(i) {
+                i = end;
+                continue;
+            }
+            // skip escaped char
+            i += if self.chars[i] == '\\' { 2 } else { 1 };
+        }
+
+        if i >= self.chars.len() {
+            return Err(self.error(ParseErrorKind::UnterminatedString, index));
+        }
+
+        let closing = self.quote_run(i);
+        let matches = closing.len() >= opening.len()
+            && opening
+                .iter()
+                .rev()
+                .zip(closing.iter())
+                .all(|(open, close)| open == close);
+
+        if !matches {
+            return Err(self.error(ParseErrorKind::MismatchedQuotes, index));
+        }
+
+        Ok(i + closing.len())
+    }
+
+    /// Skips an interpolation like `${name}` starting at `index`, which can have strings and braces in it
+    /// # Returns
+    /// - Index right after the interpolation, `None` if it isn't one
+    fn interpolation(&self, index: usize) -> Option<usize> {
+        let currency = &self.locale.currency;
+        let currency_len = currency.chars().count();
+
+        let mut i = index;
+        if !self.locale.suffix {
+            if !self.starts_with(i, currency) {
+                return None;
+            }
+            i += currency_len;
+        }
+        if self.chars.get(i) != Some(&'{') {
+            return None;
+        }
+        i += 1;
+
+        let mut depth = 0usize;
+        loop {
+            match *self.chars.get(i)? {
+                '}' if depth == 0 => break,
+                '}' => depth -= 1,
+                '{' => depth += 1,
+                ch if is_quote(ch) => {
+                    i = self.string(i).ok()?;
+                    continue;
+                }
+                _ => (),
+            }
+            i += 1;
+        }
+        i += 1;
+
+        if self.locale.suffix {
+            if !self.starts_with(i, currency) {
+                return None;
+            }
+            i += currency_len;
+        }
+        Some(i)
+    }
+
+    fn check(&self) -> Result<(), Error> {
+        let mut scopes = Vec::new();
+        let mut i = 0;
+
+        while i < self.chars.len() {
+            let ch = self.chars[i];
+
+            if self.starts_with(i, "//") {
+                while i < self.chars.len() && self.chars[i] != '\n' {
+                    i += 1;
+                }
+                continue;
+            }
+
+            if self.starts_with(i, "/*") {
+                let start = i;
+                i += 2;
+                while !self.starts_with(i, "*/") {
+                    if i >= self.chars.len() {
+                        return Err(self.error(ParseErrorKind::UnterminatedBlockComment, start));
+                    }
+                    i += 1;
+                }
+                i += 2;
+                continue;
+            }
+
+            if is_quote(ch) && (i == 0 || is_token_boundary(self.chars[i - 1])) {
+                i = self.string(i)?;
+                continue;
+            }
+
+            match ch {
+                '{' => scopes.push(i),
+                '}' if scopes.pop().is_none() => {
+                    return Err(self.error(ParseErrorKind::UnexpectedScopeEnd, i));
+                }
+                _ => (),
+            }
+
+            i += 1;
+        }
+
+        // the outermost one is the one that is missing the end
+        if let Some(start) = scopes.first() {
+            return Err(self.error(ParseErrorKind::UnclosedScope, *start));
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks the code for problems, returning the first one found
+/// - The locale decides which strings have interpolations in them
+pub fn check(code: &str, locale: &Locale) -> Result<(), Error> {
+    Diagnostics::new(code, locale).check()
+}
+
+/// Reports the code that is left after the last statement that could be parsed
+/// # Arguments
+/// - `code`: the code that was evaluated
+/// - `line`: line of the whole source code that `code` starts at
+/// # Returns
+/// - `None` if only whitespace and comments are left
+pub fn leftover<T>(code: &str, line: usize, rest: Position<T>) -> Option<Error> {
+    let (rest, _) = ws::<_, ()>(rest).unwrap();
+    if rest.input.is_empty() {
+        return None;
+    }
+
+    let kind = if rest.input.starts_with("/*") {
+        ParseErrorKind::UnterminatedBlockComment
+    } else {
+        ParseErrorKind::UnparsedStatement
+    };
+    let snippet = rest
+        .line
+        .checked_sub(line)
+        .and_then(|i| code.lines().nth(i))
+        .unwrap_or_default();
+    Some(Error::Parse {
+        kind,
+        line: rest.line,
+        column: rest.column,
+        snippet: snippet.to_string(),
+    })
+}
@@ -1,4 +1,4 @@
-use crate::interpreter::static_analysis::HoistedVarInfo;
+use crate::{interpreter::static_analysis::HoistedVarInfo, parsers::types::Position, Interpreter};
 
 use super::Analysis;
 
@@ -274,3 +274,21 @@ const const value<-1> = 5!
     };
     assert_eq!(var, &var_expected);
 }
+
+#[test]
+fn hoisted_eval_error_not_traced() {
+    let interpreter = Interpreter::new();
+    interpreter
+        .eval("function fail() => {\n   print(null.a)!\n}!")
+        .unwrap();
+
+    let code = "const const x<-1> = fail()!";
+    let binding = (&interpreter, code);
+    let var = HoistedVarInfo {
+        identifier: "x".to_string(),
+        hoisted_line: 0,
+        decl_index: 0,
+    };
+    assert!(var.eval(Position::new_with_extra(code, &binding)).is_none());
+    assert!(interpreter.state.take_error_trace().is_none());
+}
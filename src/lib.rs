@@ -10,6 +10,15 @@
 //! let code = "print(12345)!";
 //! Interpreter::new_eval(code).unwrap();
 //! ```
+//!
+//! ## Capturing output
+//! ```
+//! # use dreamberd_noodles_interpreter::interpreter::{runtime::io::BufferIo, *};
+//! let io = BufferIo::with_input(["world"]);
+//! let interpreter = Interpreter::new().with_io(io.clone());
+//! interpreter.eval("var var name = input \"name? \"!\nprint name!").unwrap();
+//! assert_eq!(io.output(), "name? world\n");
+//! ```
 
 #[cfg(feature = "mimalloc")]
 use mimalloc::MiMalloc;
@@ -20,6 +29,7 @@ static GLOBAL: MiMalloc = MiMalloc;
 
 pub use interpreter::error::*;
 pub use interpreter::*;
+pub use prelude::Wrapper;
 
 pub mod interpreter;
 mod prelude;
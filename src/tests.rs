@@ -1,10 +1,44 @@
 mod array;
+mod async_function;
+mod class;
+mod coercion;
 mod conditional;
+mod delete;
 mod expression;
+mod files;
 mod full_code;
 mod function;
+mod heap;
 mod life_time;
+mod limits;
 mod object;
+mod signal;
 mod stdlib;
+mod store;
+mod string;
+mod symbol;
 mod syntax;
+mod template;
 mod variable;
+
+use crate::{
+    interpreter::error::Error,
+    runtime::{self, io::BufferIo},
+    Interpreter,
+};
+
+/// Evaluates the code and checks everything printed
+fn interpreter_test_output(code: &str, expected: &str) {
+    let io = BufferIo::new();
+    let interpreter = Interpreter::new().with_io(io.clone());
+    interpreter.eval(code).unwrap();
+    assert_eq!(io.output(), expected);
+}
+
+/// Evaluates the code, which has to fail while running, and gets the error
+fn eval_error(interpreter: &Interpreter, code: &str) -> runtime::Error {
+    match interpreter.eval(code) {
+        Err(Error::EvalError { error, .. }) => error,
+        res => panic!("expected eval error, got {res:?}"),
+    }
+}
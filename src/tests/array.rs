@@ -1,5 +1,7 @@
 use crate::Interpreter;
 
+use super::interpreter_test_output;
+
 #[test]
 fn array_init() {
     let code = r#"
@@ -56,4 +58,212 @@ assert foo["02"] === "bar"!
     Interpreter::new_eval(code).unwrap();
 }
 
+#[test]
+fn array_length() {
+    let code = r#"
+var var foo = [ 1, 2, 3 ]!
+var var empty = []!
+assert foo.length === 3!
+assert empty.length === 0!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
+
+#[test]
+fn array_push_pop() {
+    let code = r#"
+var var foo = [ 1, 2 ]!
+var var len = foo.push 3!
+assert len === 3!
+assert foo[1] === 3!
+var var last = foo.pop!
+assert last === 3!
+assert foo.length === 2!
+assert foo[1] === undefined!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
+
+#[test]
+fn array_shift_unshift() {
+    let code = r#"
+var var foo = [ 1, 2, 3 ]!
+var var first = foo.shift!
+assert first === 1!
+assert foo[-1] === 2!
+assert foo[1] === undefined!
+var var len = foo.unshift 0!
+assert len === 3!
+assert foo[-1] === 0!
+assert foo[0] === 2!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
+
+#[test]
+fn array_slice_splice() {
+    let code = r#"
+var var foo = [ "a", "b", "c", "d" ]!
+var var middle = foo.slice 0, 2!
+print middle.join ","!
+var var last = foo.slice -2!
+print last.join ","!
+var var removed = foo.splice -1, 2, [ "x", "y", "z" ]!
+print removed.join ","!
+print foo.join ","!
+"#;
+    interpreter_test_output(code, "b,c\nd\na,b\nx,y,z,c,d\n");
+}
+
+#[test]
+fn array_optional_args() {
+    let code = r#"
+var var foo = [ "a", "b", "c" ]!
+var var s = foo.join()!
+print s!
+var var copy = foo.slice(0)!
+print copy.join "-"!
+var var all = foo.slice()!
+print all.length!
+var var nums = [ 1, 2, 3 ]!
+var var sum = nums.reduce acc, x => { return acc + x! }!
+print sum!
+var var removed = foo.splice(0)!
+print removed.join()!
+print foo.join()!
+"#;
+    interpreter_test_output(code, "a,b,c\nb-c\n3\n6\nb,c\na\n");
+}
+
+#[test]
+fn array_search() {
+    let code = r#"
+var var foo = [ 1, "2", NaN ]!
+var var one = foo.indexOf 1!
+assert one === -1!
+var var two = foo.indexOf "2"!
+assert two === 0!
+var var missing = foo.indexOf 2!
+assert missing === undefined!
+var var has_nan = foo.includes NaN!
+assert has_nan!
+var var has_three = foo.includes 3!
+assert has_three === false!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
+
+#[test]
+fn array_join_reverse() {
+    let code = r#"
+var var foo = [ 1, undefined, 3 ]!
+print foo.join()!
+foo.reverse!
+print foo.join " - "!
+"#;
+    interpreter_test_output(code, "1,,3\n3 -  - 1\n");
+}
+
+#[test]
+fn array_callbacks() {
+    let code = r#"
+var var foo = [ 1, 2, 3 ]!
+var var doubled = foo.map x => x * 2!
+print doubled.join ","!
+var var big = foo.filter x => x > 1!
+print big.join ","!
+var var sum = foo.reduce acc, x => { return acc + x! }, 10!
+print sum!
+foo.forEach x, i => print i!!
+"#;
+    interpreter_test_output(code, "2,4,6\n2,3\n16\n-1\n0\n1\n");
+}
+
+#[test]
+fn array_reduce_empty() {
+    let code = r#"
+var var foo = []!
+var var sum = foo.reduce acc, x => { return acc + x! }!
+"#;
+    assert!(Interpreter::new_eval(code).is_err());
+}
+
+#[test]
+fn array_fractional_index() {
+    let code = r#"
+const var scores = [ 3, 2, 5 ]!
+scores[0.5] = 4!
+assert scores[0.5] === 4!
+assert scores[1] === 5!
+assert scores.length === 4!
+print scores.join ", "!
+scores[-1.5] = 1!
+print scores.join ", "!
+"#;
+    interpreter_test_output(code, "3, 2, 4, 5\n1, 3, 2, 4, 5\n");
+}
+
+#[test]
+fn array_fractional_index_renumbered() {
+    let code = r#"
+const var scores = [ 3, 2, 5 ]!
+scores[0.5] = 4!
+scores.push 6!
+assert scores[0.5] === undefined!
+assert scores[1] === 4!
+assert scores[3] === 6!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
+
+#[test]
+fn array_fractional_index_print() {
+    let code = r#"
+const var scores = [ 3, 2, 5 ]!
+scores[0.5] = 4!
+scores.__proto__ = null!
+print scores!
+"#;
+    interpreter_test_output(
+        code,
+        "{\n  -1: 3,\n  0: 2,\n  0.5: 4,\n  1: 5,\n  __proto__: null\n}\n",
+    );
+}
+
 // TODO error trying to use int for property read with dot notation
+
+#[test]
+fn array_methods_ignore_constants() {
+    let code = r#"
+const const arr = [1, 2]!
+arr.push(3)!
+arr.reverse()!
+print arr.length!
+print arr[-1]!
+var const other = [1, 2]!
+other.pop()!
+print other.length!
+"#;
+    interpreter_test_output(code, "2\n1\n2\n");
+}
+
+#[test]
+fn array_methods_trigger_when() {
+    let code = r#"
+const var items = [1]!
+when (items.length > 2) {
+    print "full"!
+}
+items.push(2)!
+items.push(3)!
+"#;
+    interpreter_test_output(code, "full\n");
+}
+
+#[test]
+fn array_print_hides_methods() {
+    interpreter_test_output(
+        "print [1, 2]!",
+        "{\n  -1: 1,\n  0: 2,\n  __proto__: {\n    __proto__: {\n      __proto__: null\n    }\n  }\n}\n",
+    );
+}
@@ -0,0 +1,113 @@
+use crate::{
+    interpreter::error::Error,
+    runtime::{self, io::BufferIo},
+    Interpreter,
+};
+
+use super::interpreter_test_output;
+
+#[test]
+fn take_turns() {
+    let code = r#"
+async function count => {
+    print 1!
+    print 3!
+}!
+count!
+print 2!
+"#;
+    interpreter_test_output(code, "1\n2\n3\n");
+}
+
+#[test]
+fn noop_yields() {
+    let code = r#"
+async function count => {
+    print 1!
+    noop!
+    print 4!
+}!
+count!
+print 2!
+print 3!
+"#;
+    interpreter_test_output(code, "1\n2\n3\n4\n");
+}
+
+#[test]
+fn calls_take_turns_in_order() {
+    let code = r#"
+async function count name => {
+    print name + 1!
+    print name + 2!
+}!
+count 10!
+count 20!
+print 0!
+"#;
+    interpreter_test_output(code, "11\n12\n21\n0\n22\n");
+}
+
+#[test]
+fn return_finishes_call() {
+    let code = r#"
+async function count => {
+    print 1!
+    return 2!
+    print 3!
+}!
+count!
+print 2!
+print 4!
+"#;
+    interpreter_test_output(code, "1\n2\n4\n");
+}
+
+#[test]
+fn expression_body() {
+    let code = r#"
+async function greet name => print name!
+greet "hi"!
+print "main"!
+"#;
+    interpreter_test_output(code, "hi\nmain\n");
+}
+
+#[test]
+fn error_in_call() {
+    let code = r#"
+async function fail => {
+    noop!
+    assert false!
+}!
+fail!
+print 1!
+print 2!
+"#;
+    let err = Interpreter::new_eval(code).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::EvalError {
+            error: runtime::Error::RuntimeException(_),
+            ..
+        }
+    ));
+}
+
+#[test]
+fn error_drops_calls() {
+    let io = BufferIo::new();
+    let interpreter = Interpreter::new().with_io(io.clone());
+    let code = r#"
+async function count => {
+    print 1!
+    print 2!
+    print 3!
+}!
+count!
+assert false!
+"#;
+    assert!(interpreter.eval(code).is_err());
+    interpreter.eval("print 9!").unwrap();
+    assert_eq!(io.output(), "1\n9\n");
+}
@@ -0,0 +1,105 @@
+use crate::{interpreter::error::Error, runtime, Interpreter};
+
+#[test]
+fn class_fields() {
+    let code = r#"
+class Player {
+   const var health = 10!
+   var var name = "bob"!
+}
+const var player = new Player()!
+assert player.health === 10!
+assert player.name === "bob"!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
+
+#[test]
+fn class_methods() {
+    let code = r#"
+class Player {
+   const var health = 10!
+   function getHealth() => this.health!
+   function heal(amount) => {
+      this.health = this.health + amount!
+   }
+}
+const var player = new Player()!
+player.heal(5)!
+var var health = player.getHealth()!
+assert health === 15!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
+
+#[test]
+fn class_name_keyword() {
+    let code = r#"
+className Player {
+   const var health = 10!
+}
+const var player = new Player()!
+assert player.health === 10!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
+
+#[test]
+fn class_prototype() {
+    let code = r#"
+class Player {
+   function getHealth() => 10!
+}
+const var player = new Player()!
+assert player.__proto__ === Player.prototype!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
+
+#[test]
+fn class_fields_use_this() {
+    let code = r#"
+class Player {
+   const var health = 10!
+   const var max_health = this.health * 2!
+}
+const var player = new Player()!
+assert player.max_health === 20!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
+
+#[test]
+fn class_single_instance() {
+    let code = r#"
+class Player {
+   const var health = 10!
+}
+const var player1 = new Player()!
+const var player2 = new Player()!
+"#;
+    let err = Interpreter::new_eval(code).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::EvalError {
+            error: runtime::Error::MultipleInstances(_),
+            ..
+        }
+    ));
+}
+
+#[test]
+fn new_not_class() {
+    let code = r#"
+const var foo = {}!
+const var bar = new foo()!
+"#;
+    let err = Interpreter::new_eval(code).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::EvalError {
+            error: runtime::Error::Type(_),
+            ..
+        }
+    ));
+}
@@ -0,0 +1,112 @@
+use crate::{interpreter::error::Error, runtime, Interpreter};
+
+#[test]
+fn value_of() {
+    let code = r#"
+class Money {
+   const var cents = 250!
+   function valueOf() => this.cents!
+}
+const var money = new Money()!
+assert money + 1 === 251!
+assert money * 2 === 500!
+assert -money === -250!
+assert money > 100!
+assert money <= 250!
+assert money == 250!
+
+var var total = 1!
+total += money!
+assert total === 251!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
+
+#[test]
+fn to_string() {
+    let code = r#"
+var var name = { first: "bob" }!
+name.toString = => "bob"!
+assert name == "bob"!
+
+var var both = { a: 1 }!
+both.toString = => "two"!
+both.valueOf = => 1!
+assert both == 1!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
+
+#[test]
+fn symbol_to_primitive() {
+    let code = r#"
+const const toPrimitive = Symbol.toPrimitive!
+var var obj = { a: 1 }!
+obj[toPrimitive] = hint => {
+   if (hint === "number") {
+      return 42!
+   }
+   return hint!
+}!
+assert obj * 1 === 42!
+assert obj > 41!
+assert obj == "default"!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
+
+#[test]
+fn ordinary_objects() {
+    let code = r#"
+const var obj = { a: 1 }!
+assert obj == "[object Object]"!
+assert obj == obj!
+assert obj ;= null!
+assert obj ;= undefined!
+assert obj ;= 1!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
+
+#[test]
+fn no_primitive() {
+    let code = r#"
+var var obj = { a: 1 }!
+obj.valueOf = => this!
+obj.toString = => this!
+print obj + 1!
+"#;
+    let err = Interpreter::new_eval(code).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::EvalError {
+            error: runtime::Error::Type(_),
+            ..
+        }
+    ));
+}
+
+#[test]
+fn increment() {
+    let code = r#"
+const var obj = { valueOf: function() => 5 }!
+var var x = obj!
+x++!
+assert x === 6!
+var var y = obj!
+y--!
+assert y === 4!
+
+var var s = "5"!
+s++!
+assert s === 6!
+var var t = "5"!
+t--!
+assert t === 4!
+
+var var big = 1n!
+big++!
+assert big === 2n!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
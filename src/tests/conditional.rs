@@ -1,5 +1,7 @@
 use crate::Interpreter;
 
+use super::interpreter_test_output;
+
 #[test]
 fn if_statement_true() {
     let code = r#"
@@ -234,3 +236,75 @@ assert b === 3!
 "#;
     Interpreter::new_eval(code).unwrap();
 }
+
+#[test]
+fn if_reads_current_value() {
+    let code = r#"
+var var x = 1!
+if true {
+    x = 2!
+    assert x === 2!
+}
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
+
+#[test]
+fn when_in_function_called_twice() {
+    let code = r#"
+var var count = 0!
+function watch() => {
+   var var a = 0!
+   when a === 1 {
+      count++!
+   }
+   a = 1!
+}!
+watch()!
+watch()!
+assert count === 2!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
+
+#[test]
+fn when_loop_ten_thousand() {
+    // deep enough to overflow the stack if each trigger nested another
+    let code = r#"
+var var i = 0!
+when i < 10000 {
+   i = i + 1!
+}
+i = 1!
+assert i === 10000!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
+
+#[test]
+#[ignore = "slow in debug builds, run with `cargo test --release -- --ignored`"]
+fn when_loop_million() {
+    let code = r#"
+var var i = 0!
+when i < 1000000 {
+   i = i + 1!
+}
+i = 1!
+assert i === 1000000!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
+
+#[test]
+fn when_triggered_after_body() {
+    // the assignment triggers the `when` again once the body is done, so each body prints its own value
+    let code = r#"
+var var i = 0!
+when i < 3 {
+   i = i + 1!
+   print i!
+}
+i = 1!
+"#;
+    interpreter_test_output(code, "2\n3\n");
+}
@@ -0,0 +1,102 @@
+use super::{eval_error, interpreter_test_output};
+use crate::{interpreter, runtime, Interpreter};
+
+fn deleted_error(code: &str) -> String {
+    let err = Interpreter::new_eval(code).unwrap_err();
+    match err {
+        interpreter::error::Error::EvalError {
+            error: runtime::Error::Deleted(name),
+            ..
+        } => name,
+        err => panic!("expected deleted error, got {err:?}"),
+    }
+}
+
+#[test]
+fn delete_variable() {
+    let code = r#"
+var var a = 1!
+delete a!
+print a!
+"#;
+    interpreter_test_output(code, "a\n");
+}
+
+#[test]
+fn delete_property() {
+    let code = r#"
+const var obj = { a: 1, b: 2 }!
+delete obj.a!
+delete obj["b"]!
+print(obj.a)!
+print(obj.b)!
+"#;
+    interpreter_test_output(code, "undefined\nundefined\n");
+}
+
+#[test]
+fn delete_primitive() {
+    let code = r#"
+delete 3!
+print(3)!
+"#;
+    assert_eq!(deleted_error(code), "3");
+}
+
+#[test]
+fn delete_keyword() {
+    let code = r#"
+delete var!
+const var a = 1!
+"#;
+    assert_eq!(deleted_error(code), "var");
+}
+
+#[test]
+fn delete_in_function_body() {
+    let code = r#"
+const const f = () => 3!
+const var a = f()!
+delete 3!
+const var b = f()!
+"#;
+    assert_eq!(deleted_error(code), "3");
+}
+
+#[test]
+fn delete_per_interpreter() {
+    Interpreter::new_eval("delete 3!").unwrap();
+    Interpreter::new_eval("assert 3 === 3!").unwrap();
+}
+
+#[test]
+fn delete_constants() {
+    let code = r#"
+const const a = 1!
+delete a!
+const const obj = { a: 1 }!
+delete obj.a!
+print a!
+print obj.a!
+"#;
+    interpreter_test_output(code, "1\n1\n");
+}
+
+#[test]
+fn strict_delete_constants() {
+    let err = eval_error(
+        &Interpreter::new().with_strict_mode(true),
+        "const const a = 1!\ndelete a!",
+    );
+    assert!(matches!(err, runtime::Error::ConstAssignment(name) if name == "a"));
+    let err = eval_error(
+        &Interpreter::new().with_strict_mode(true),
+        "const const obj = { a: 1 }!\ndelete obj.a!",
+    );
+    assert!(matches!(err, runtime::Error::ConstMutation(name) if name == "obj"));
+
+    let interpreter = Interpreter::new().with_strict_mode(true);
+    interpreter
+        .eval("var var a = 1!\ndelete a!\nconst var obj = { a: 1 }!\ndelete obj.a!")
+        .unwrap();
+}
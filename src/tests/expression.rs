@@ -1,4 +1,5 @@
-use crate::Interpreter;
+use super::eval_error;
+use crate::{interpreter::error::Error, runtime, Interpreter};
 
 #[test]
 fn int_comparisons() {
@@ -246,3 +247,94 @@ assert value!
 "#;
     Interpreter::new_eval(code).unwrap();
 }
+
+#[test]
+fn string_concat() {
+    let code = r#"
+assert "a" + "b" === "ab"!
+assert "a" + 1 === "a1"!
+assert 1 + "a" === "1a"!
+assert "big" + 1n === "big1"!
+assert "a" + true + null + undefined === "atruenullundefined"!
+assert 1 + 2 + "3" === "33"!
+
+var var text = "count: "!
+text += 5!
+assert text === "count: 5"!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
+
+#[test]
+fn bigint_arithmetic() {
+    let code = r#"
+assert 1n + 2n === 3n!
+assert 5n - 7n === -2n!
+assert 6n * 7n === 42n!
+assert 10n / 3n === 3n!
+assert -7n % 3n === -1n!
+assert 2n ^ 64n === 18446744073709551616n!
+assert -(3n) === -3n!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
+
+#[test]
+fn bigint_division_by_zero() {
+    for code in [
+        "print 1n / 0n!",
+        "print 1n % 0n!",
+        "var var a = 1n!\na /= 0n!",
+    ] {
+        let err = Interpreter::new_eval(code).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                Error::EvalError {
+                    error: runtime::Error::Range(_),
+                    ..
+                }
+            ),
+            "{code} didn't fail with a RangeError: {err:?}"
+        );
+    }
+}
+
+#[test]
+fn bigint_pow_too_big() {
+    for code in [
+        "print 2n ^ 4000000000n!",
+        "var var a = 3n!\na ^= 100000000n!",
+    ] {
+        let err = eval_error(&Interpreter::new(), code);
+        assert!(
+            matches!(err, runtime::Error::Range(_)),
+            "{code} didn't fail with a RangeError: {err:?}"
+        );
+    }
+
+    let code = r#"
+assert 1n ^ 4000000000n === 1n!
+assert -1n ^ 4000000001n === -1n!
+assert 0n ^ 4000000000n === 0n!
+var var big = 2n ^ 1000n!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
+
+#[test]
+fn bigint_mixed_types() {
+    for code in ["print 1n + 1!", "print 2 * 1n!", "print 1n - true!"] {
+        let err = Interpreter::new_eval(code).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                Error::EvalError {
+                    error: runtime::Error::Type(_),
+                    ..
+                }
+            ),
+            "{code} didn't fail with a TypeError: {err:?}"
+        );
+    }
+}
@@ -0,0 +1,61 @@
+use super::interpreter_test_output;
+use crate::{
+    interpreter::error::Error,
+    runtime::{self, error::Location},
+    Interpreter,
+};
+
+#[test]
+fn files_have_own_scope() {
+    let code = r#"
+const const a = 1!
+print a!
+=====
+print a!
+"#;
+    interpreter_test_output(code, "1\na\n");
+}
+
+#[test]
+fn export_import() {
+    let code = r#"
+function add left, right => left + right!
+export add to "main.db"!
+======= main.db ==
+import add!
+const var x = add 1, 2!
+print x!
+"#;
+    interpreter_test_output(code, "3\n");
+}
+
+#[test]
+fn import_not_exported() {
+    let code = r#"
+const const a = 1!
+export a to "other.db"!
+===== main.db =====
+import a!
+"#;
+    let err = Interpreter::new_eval(code).unwrap_err();
+    let Error::EvalError {
+        error: runtime::Error::NotExported(name),
+        trace: Some(trace),
+    } = err
+    else {
+        panic!("expected not exported error, got {err:?}");
+    };
+    assert_eq!(name, "a");
+    assert_eq!(trace.location, Location { line: 5, column: 1 });
+}
+
+#[test]
+fn eval_file() {
+    let interpreter = Interpreter::new();
+    interpreter
+        .eval_file("lib.db", r#"const const a = 5! export a to "main.db"!"#)
+        .unwrap();
+    interpreter
+        .eval_file("main.db", "import a! assert a === 5!")
+        .unwrap();
+}
@@ -1,4 +1,18 @@
-use crate::Interpreter;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use crate::{
+    interpreter::error::Error,
+    runtime::{
+        error::{Location, StackFrame},
+        value::Value,
+    },
+    Interpreter,
+};
+
+use super::interpreter_test_output;
 
 #[test]
 fn declare_function_no_args() {
@@ -193,3 +207,153 @@ assert(added_type === "string")!
     // implement it
     Interpreter::new_eval(code).unwrap();
 }
+
+#[test]
+fn register_fn_closure() {
+    let counter = Arc::new(AtomicUsize::new(0));
+    let interpreter = Interpreter::new();
+    let counter_fn = Arc::clone(&counter);
+    interpreter.register_fn("count", Some(1), move |_, args| {
+        let by = f64::try_from(args[0].as_ref())? as usize;
+        let count = counter_fn.fetch_add(by, Ordering::SeqCst) + by;
+        Ok(Value::Number(count as f64))
+    });
+
+    let code = r#"
+count 1!
+var var total = count 2!
+assert total === 3!
+"#;
+    interpreter.eval(code).unwrap();
+    assert_eq!(counter.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn register_fn_no_args() {
+    let interpreter = Interpreter::new();
+    interpreter.register_fn("answer", Some(0), |_, _| Ok(Value::Number(42.0)));
+
+    let code = r#"
+var var result = answer!
+assert result === 42!
+"#;
+    interpreter.eval(code).unwrap();
+}
+
+#[test]
+fn error_trace_top_level() {
+    let code = r#"
+var var foo = null!
+  print(foo.bar)!
+"#;
+    let Err(Error::EvalError {
+        trace: Some(trace), ..
+    }) = Interpreter::new_eval(code)
+    else {
+        panic!("expected an error with a trace");
+    };
+    assert_eq!(trace.location, Location { line: 3, column: 3 });
+    assert!(trace.stack.is_empty());
+}
+
+#[test]
+fn error_trace_call_stack() {
+    let code = r#"
+function inner(x) => {
+   print(x.a)!
+}!
+function outer() => {
+   inner(null)!
+}!
+outer()!
+"#;
+    let Err(Error::EvalError {
+        trace: Some(trace), ..
+    }) = Interpreter::new_eval(code)
+    else {
+        panic!("expected an error with a trace");
+    };
+    assert_eq!(trace.location, Location { line: 3, column: 4 });
+    assert_eq!(
+        trace.stack,
+        vec![
+            StackFrame {
+                name: "outer".to_string(),
+                call_site: Location { line: 8, column: 1 },
+            },
+            StackFrame {
+                name: "inner".to_string(),
+                call_site: Location { line: 6, column: 4 },
+            },
+        ]
+    );
+}
+
+#[test]
+fn error_trace_is_cleared() {
+    let interpreter = Interpreter::new();
+    let code = r#"
+function fail() => {
+   print(null.a)!
+}!
+"#;
+    interpreter.eval(code).unwrap();
+    assert!(interpreter.eval("fail()!").is_err());
+
+    // the failed call doesn't leave its frame behind
+    let Err(Error::EvalError {
+        trace: Some(trace), ..
+    }) = interpreter.eval("print(null.a)!")
+    else {
+        panic!("expected an error with a trace");
+    };
+    assert!(trace.stack.is_empty());
+}
+
+#[test]
+fn recursive_function() {
+    let code = r#"
+function fib(n) => {
+   if n < 2 {
+      return n!
+   }
+   var var a = fib(n - 1)!
+   var var b = fib(n - 2)!
+   return a + b!
+}!
+var var result = fib(10)!
+assert result === 55!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
+
+#[test]
+fn return_in_if() {
+    let code = r#"
+function check(n) => {
+   if n === 1 {
+      return "one"!
+   }
+   return "other"!
+}!
+var var one = check(1)!
+var var other = check(2)!
+assert one === "one"!
+assert other === "other"!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
+
+#[test]
+fn cached_body_reparsed() {
+    // `x` is an implicit string until it is declared
+    let code = r#"
+function show() => {
+   print x!
+}!
+show!
+var var x = 5!
+show!
+"#;
+    interpreter_test_output(code, "x\n5\n");
+}
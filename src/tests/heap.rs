@@ -0,0 +1,96 @@
+use super::eval_error;
+use crate::{
+    runtime::{self, budget::Limits},
+    Interpreter,
+};
+
+#[test]
+fn collect_cycle() {
+    let interpreter = Interpreter::new();
+    let before = interpreter.heap_stats();
+
+    let code = r#"
+var var a = { b: 1 }!
+a.self = a!
+delete a!
+"#;
+    interpreter.eval(code).unwrap();
+    assert_eq!(interpreter.heap_stats().objects, before.objects + 1);
+
+    assert_eq!(interpreter.collect_garbage(), 1);
+    assert_eq!(interpreter.heap_stats(), before);
+}
+
+#[test]
+fn keep_reachable_cycle() {
+    let interpreter = Interpreter::new();
+
+    let code = r#"
+var var a = { b: 1 }!
+var var c = { a: a }!
+a.c = c!
+"#;
+    interpreter.eval(code).unwrap();
+    assert_eq!(interpreter.collect_garbage(), 0);
+
+    interpreter.eval("assert a.c.a.b === 1!").unwrap();
+}
+
+#[test]
+fn object_limit() {
+    let interpreter = Interpreter::new();
+    let objects = interpreter.heap_stats().objects;
+    interpreter.set_limits(Limits {
+        max_objects: Some(objects + 50),
+        ..Default::default()
+    });
+
+    // every call keeps its object alive
+    let code = r#"
+function f n => {
+    const const obj = { n: n }!
+    return f n!
+}!
+f 1!
+"#;
+    let err = eval_error(&interpreter, code);
+    assert!(matches!(err, runtime::Error::ObjectLimit(_)));
+}
+
+#[test]
+fn garbage_is_not_counted() {
+    let interpreter = Interpreter::new();
+    let objects = interpreter.heap_stats().objects;
+    interpreter.set_limits(Limits {
+        max_objects: Some(objects + 1),
+        ..Default::default()
+    });
+
+    let code = r#"
+var var a = { b: 1 }!
+a.self = a!
+delete a!
+var var c = { d: 1 }!
+c.self = c!
+"#;
+    interpreter.eval(code).unwrap();
+}
+
+#[test]
+fn property_limit() {
+    let interpreter = Interpreter::new();
+    let properties = interpreter.heap_stats().properties;
+    interpreter.set_limits(Limits {
+        max_properties: Some(properties + 3),
+        ..Default::default()
+    });
+
+    let code = r#"
+var var a = { b: 1, c: 2 }!
+a.d = 3!
+a.e = 4!
+print a!
+"#;
+    let err = eval_error(&interpreter, code);
+    assert!(matches!(err, runtime::Error::PropertyLimit(_)));
+}
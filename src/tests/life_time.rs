@@ -1,4 +1,9 @@
-use crate::Interpreter;
+use std::time::Duration;
+
+use crate::{
+    runtime::{clock::VirtualClock, io::BufferIo},
+    Interpreter,
+};
 
 #[test]
 fn seconds() {
@@ -21,3 +26,143 @@ assert foo === "baz"!
     "#;
     Interpreter::new_eval(input).unwrap();
 }
+
+#[test]
+fn seconds_virtual_clock() {
+    let clock = VirtualClock::new();
+    let interpreter = Interpreter::new().with_clock(clock.clone());
+    interpreter
+        .eval(
+            r#"
+const const value<1s> = 1!
+sleep 999!
+assert value === 1!
+"#,
+        )
+        .unwrap();
+
+    clock.advance(Duration::from_millis(1));
+    interpreter
+        .eval("const const gone = value!\nassert gone === \"value\"!")
+        .unwrap();
+}
+
+#[test]
+fn restores_shadowed() {
+    let input = r#"
+const const name = "Luke"!
+const const name<2s> = "Lu"!
+const const name<1s> = "L"!
+assert name === "L"!
+sleep 1000!
+assert name === "Lu"!
+sleep 1000!
+assert name === "Luke"!
+    "#;
+    Interpreter::new()
+        .with_clock(VirtualClock::new())
+        .eval(input)
+        .unwrap();
+}
+
+#[test]
+fn restores_shadowed_out_of_order() {
+    let input = r#"
+const const name = "Luke"!
+const const name<1s> = "Lu"!
+const const name<2s> = "L"!
+sleep 1000!
+assert name === "L"!
+sleep 1000!
+assert name === "Luke"!
+    "#;
+    Interpreter::new()
+        .with_clock(VirtualClock::new())
+        .eval(input)
+        .unwrap();
+}
+
+#[test]
+fn expiry_notifies_when() {
+    let io = BufferIo::new();
+    let interpreter = Interpreter::new()
+        .with_io(io.clone())
+        .with_clock(VirtualClock::new());
+    interpreter
+        .eval(
+            r#"
+var var score = 1!
+when score === 1 {
+    print "back"!
+}
+var var score<1s> = 2!
+var var total<0.5s> = 3!
+print "waiting"!
+sleep 2000!
+print "done"!
+"#,
+        )
+        .unwrap();
+    assert_eq!(io.output(), "waiting\nback\ndone\n");
+}
+
+#[test]
+fn expires_in_order_while_sleeping() {
+    let io = BufferIo::new();
+    let interpreter = Interpreter::new()
+        .with_io(io.clone())
+        .with_clock(VirtualClock::new());
+    interpreter
+        .eval(
+            r#"
+var var first = 0!
+var var second = 0!
+when second === 0 {
+    print "second"!
+}
+when first === 0 {
+    print "first"!
+}
+var var second<2s> = 2!
+var var first<1s> = 1!
+sleep 5000!
+"#,
+        )
+        .unwrap();
+    assert_eq!(io.output(), "first\nsecond\n");
+}
+
+#[test]
+fn expired_object_collected() {
+    let clock = VirtualClock::new();
+    let interpreter = Interpreter::new().with_clock(clock.clone());
+    let before = interpreter.heap_stats();
+
+    interpreter
+        .eval("const const cache<1s> = { items: 1 }!")
+        .unwrap();
+    interpreter.collect_garbage();
+    assert_eq!(interpreter.heap_stats().objects, before.objects + 1);
+
+    clock.advance(Duration::from_secs(1));
+    interpreter.eval("noop!").unwrap();
+    interpreter.collect_garbage();
+    assert_eq!(interpreter.heap_stats(), before);
+}
+
+#[test]
+fn set_clock_keeps_time_left() {
+    let clock = VirtualClock::new();
+    let interpreter = Interpreter::new().with_clock(clock.clone());
+    interpreter.eval("const const value<1s> = 1!").unwrap();
+    clock.advance(Duration::from_millis(500));
+
+    let other = VirtualClock::new();
+    other.advance(Duration::from_secs(10));
+    interpreter.set_clock(other.clone());
+    interpreter.eval("assert value === 1!").unwrap();
+    other.advance(Duration::from_millis(500));
+    interpreter
+        .eval("const const gone = value!\nassert gone === \"value\"!")
+        .unwrap();
+}
@@ -0,0 +1,141 @@
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use super::eval_error;
+use crate::{
+    runtime::{self, budget::Limits, io::BufferIo},
+    Interpreter,
+};
+
+#[test]
+fn step_limit() {
+    let io = BufferIo::new();
+    let interpreter = Interpreter::new().with_io(io.clone()).with_limits(Limits {
+        max_steps: Some(2),
+        ..Default::default()
+    });
+
+    let code = r#"
+print 1!
+print 2!
+print 3!
+"#;
+    let err = eval_error(&interpreter, code);
+    assert!(matches!(err, runtime::Error::StepLimit(2)));
+    assert_eq!(io.output(), "1\n2\n");
+
+    // the steps are counted for each eval
+    interpreter.eval("print 4!").unwrap();
+}
+
+#[test]
+fn deadline() {
+    let interpreter = Interpreter::new().with_limits(Limits {
+        deadline: Some(Instant::now()),
+        ..Default::default()
+    });
+
+    let err = eval_error(&interpreter, "print 1!");
+    assert!(matches!(err, runtime::Error::Deadline));
+}
+
+#[test]
+fn call_depth() {
+    let interpreter = Interpreter::new().with_limits(Limits {
+        max_call_depth: Some(20),
+        ..Default::default()
+    });
+
+    let code = r#"
+function f n => {
+    return f n!
+}!
+f 1!
+"#;
+    let err = eval_error(&interpreter, code);
+    assert!(matches!(err, runtime::Error::CallDepth(20)));
+}
+
+#[test]
+fn when_depth() {
+    let interpreter = Interpreter::new().with_limits(Limits {
+        max_when_depth: Some(10),
+        ..Default::default()
+    });
+
+    // the condition never becomes false
+    let code = r#"
+var var i = 0!
+when i > 0 {
+    i = i + 1!
+}
+i = 1!
+"#;
+    let err = eval_error(&interpreter, code);
+    assert!(matches!(err, runtime::Error::WhenDepth(10)));
+}
+
+#[test]
+fn cancel() {
+    let interpreter = Interpreter::new();
+    let handle = interpreter.cancel_handle();
+
+    thread::spawn(move || handle.cancel()).join().unwrap();
+    let err = eval_error(&interpreter, "const var a = 1!");
+    assert!(matches!(err, runtime::Error::Cancelled));
+
+    interpreter.cancel_handle().reset();
+    interpreter.eval("const var a = 1!").unwrap();
+}
+
+#[test]
+fn deadline_during_sleep() {
+    let interpreter = Interpreter::new().with_limits(Limits {
+        deadline: Some(Instant::now() + Duration::from_millis(50)),
+        ..Default::default()
+    });
+
+    let start = Instant::now();
+    let err = eval_error(&interpreter, "sleep 10000!");
+    assert!(matches!(err, runtime::Error::Deadline));
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn cancel_during_sleep() {
+    let interpreter = Interpreter::new();
+    let handle = interpreter.cancel_handle();
+
+    let canceller = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        handle.cancel();
+    });
+    let start = Instant::now();
+    let err = eval_error(&interpreter, "sleep 10000!");
+    canceller.join().unwrap();
+    assert!(matches!(err, runtime::Error::Cancelled));
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn step_limit_in_if() {
+    let io = BufferIo::new();
+    let interpreter = Interpreter::new().with_io(io.clone()).with_limits(Limits {
+        max_steps: Some(4),
+        ..Default::default()
+    });
+
+    // the braces of the body are steps too
+    let code = r#"
+if (true) {
+    print 1!
+    print 2!
+    print 3!
+}
+"#;
+    let err = eval_error(&interpreter, code);
+    assert!(matches!(err, runtime::Error::StepLimit(4)));
+    assert_eq!(io.output(), "1\n2\n");
+}
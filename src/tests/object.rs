@@ -99,7 +99,10 @@ print foo.a!
     let err = Interpreter::new_eval(code).unwrap_err();
     assert!(matches!(
         err,
-        interpreter::error::Error::EvalError(runtime::Error::Type(_))
+        interpreter::error::Error::EvalError {
+            error: runtime::Error::Type(_),
+            ..
+        }
     ));
 }
 
@@ -135,3 +138,69 @@ assert foo === baz.test!
 "#;
     Interpreter::new_eval(code).unwrap();
 }
+
+#[test]
+fn prototype_isolated() {
+    let patched = Interpreter::new();
+    patched
+        .eval(
+            r#"
+Object.prototype.foo = 1!
+var var bar = {}!
+assert bar.foo === 1!
+"#,
+        )
+        .unwrap();
+
+    let code = r#"
+var var bar = {}!
+assert bar.foo === undefined!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
+
+#[test]
+fn prototype_shared_realm() {
+    let first = Interpreter::new();
+    let second = Interpreter::new_with_realm(first.realm());
+
+    first.eval("Object.prototype.foo = 1!").unwrap();
+    let code = r#"
+var var bar = {}!
+assert bar.foo === 1!
+var var baz = [1, 2]!
+assert baz.foo === 1!
+"#;
+    second.eval(code).unwrap();
+}
+
+#[test]
+fn bracket_notation_variable() {
+    let code = r#"
+var var foo = { a: 1 }!
+const const key = "b"!
+foo[key] = 2!
+assert foo[key] === 2!
+assert foo.b === 2!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
+
+#[test]
+fn bracket_notation_converted_keys() {
+    let code = r#"
+var var obj = { }!
+obj[true] = 1!
+obj[null] = 2!
+obj[undefined] = 3!
+assert obj["true"] === 1!
+assert obj["null"] === 2!
+assert obj["undefined"] === 3!
+
+const const key = { toString: () => "b" }!
+obj[key] = 4!
+assert obj.b === 4!
+assert obj[key] === 4!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
@@ -0,0 +1,78 @@
+use super::interpreter_test_output;
+
+#[test]
+fn get_and_set() {
+    let code = r#"
+const var [getScore, setScore] = use 1!
+print getScore()!
+setScore 2!
+print getScore()!
+"#;
+    interpreter_test_output(code, "1\n2\n");
+}
+
+#[test]
+fn set_triggers_when() {
+    let code = r#"
+const var [getScore, setScore] = use 0!
+when getScore() === 9 {
+    print "win"!
+}
+setScore 3!
+setScore 9!
+"#;
+    interpreter_test_output(code, "win\n");
+}
+
+#[test]
+fn same_value_does_not_trigger() {
+    let code = r#"
+const var [getScore, setScore] = use 0!
+when getScore() > 0 {
+    print getScore()!
+}
+setScore 1!
+setScore 1!
+setScore 2!
+"#;
+    interpreter_test_output(code, "1\n2\n");
+}
+
+#[test]
+fn signals_are_separate() {
+    let code = r#"
+const var [getA, setA] = use 0!
+const var [getB, setB] = use 0!
+when getA() > 0 {
+    print "a"!
+}
+setB 1!
+setA 1!
+print getB()!
+"#;
+    interpreter_test_output(code, "a\n1\n");
+}
+
+#[test]
+fn combined_function() {
+    let code = r#"
+const var score = use(0)!
+print score()!
+score(9)!
+print score()!
+"#;
+    interpreter_test_output(code, "0\n9\n");
+}
+
+#[test]
+fn combined_function_triggers_when() {
+    let code = r#"
+const var score = use 0!
+when score() === 9 {
+    print "win"!
+}
+score(3)!
+score(9)!
+"#;
+    interpreter_test_output(code, "win\n");
+}
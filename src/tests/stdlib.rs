@@ -1,4 +1,6 @@
-use crate::Interpreter;
+use crate::{runtime::io::BufferIo, Interpreter};
+
+use super::interpreter_test_output;
 
 #[test]
 fn assert_success() {
@@ -12,11 +14,26 @@ fn assert_fail() {
     assert!(Interpreter::new_eval(code).is_err());
 }
 
-// TODO: implmenet
-// #[test]
-// fn print() {
-//     interpreter_test_output("print 1!", "1\n");
-// }
+#[test]
+fn print() {
+    interpreter_test_output("print 1!", "1\n");
+}
+
+#[test]
+fn input() {
+    let io = BufferIo::with_input(["foo"]);
+    let interpreter = Interpreter::new().with_io(io.clone());
+    interpreter
+        .eval(
+            r#"
+var var name = input "name: "!
+assert name === "foo"!
+print name!
+"#,
+        )
+        .unwrap();
+    assert_eq!(io.output(), "name: foo\n");
+}
 
 #[test]
 fn get_typeof() {
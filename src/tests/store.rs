@@ -0,0 +1,113 @@
+use std::fs;
+
+use super::eval_error;
+use crate::{
+    runtime::{
+        self,
+        store::{FileStore, MemoryStore},
+    },
+    Interpreter,
+};
+
+#[test]
+fn shared_between_interpreters() {
+    let store = MemoryStore::new();
+    let first = Interpreter::new().with_store(store.clone());
+    let second = Interpreter::new().with_store(store);
+
+    first.eval("const const const pi = 3.14!").unwrap();
+    second.eval("assert pi === 3.14!").unwrap();
+}
+
+#[test]
+fn redeclare() {
+    let store = MemoryStore::new();
+    let first = Interpreter::new().with_store(store.clone());
+    let second = Interpreter::new().with_store(store);
+
+    first.eval("const const const name = \"Luke\"!").unwrap();
+    let err = eval_error(&second, "const const const name = \"Lu\"!");
+    assert!(matches!(err, runtime::Error::Redeclared(name) if name == "name"));
+    let err = eval_error(&first, "const const const name = \"Lu\"!");
+    assert!(matches!(err, runtime::Error::Redeclared(_)));
+    second.eval("assert name === \"Luke\"!").unwrap();
+}
+
+#[test]
+fn shadowing() {
+    let interpreter = Interpreter::new().with_store(MemoryStore::new());
+    let code = r#"
+const const const count = 1!
+count = 2!
+assert count === 1!
+function shadow() => {
+   var var count = 3!
+   return count!
+}!
+var var shadowed = shadow()!
+assert shadowed === 3!
+assert count === 1!
+"#;
+    interpreter.eval(code).unwrap();
+}
+
+#[test]
+fn only_primitives() {
+    let interpreter = Interpreter::new().with_store(MemoryStore::new());
+    let err = eval_error(&interpreter, "const const const obj = { }!");
+    assert!(matches!(err, runtime::Error::Type(_)));
+}
+
+#[test]
+fn file_store() {
+    let dir = std::env::temp_dir().join(format!("noodles-store-test-{}", std::process::id()));
+    let first = Interpreter::new().with_store(FileStore::new(&dir));
+    let second = Interpreter::new().with_store(FileStore::new(&dir));
+
+    let code = r#"
+const const const big = 12345678901234567890n!
+const const const text = "a b\nc"!
+const const const nothing = null!
+"#;
+    first.eval(code).unwrap();
+    second
+        .eval(
+            r#"
+assert big === 12345678901234567890n!
+assert text === "a b\nc"!
+assert nothing === null!
+"#,
+        )
+        .unwrap();
+    let err = eval_error(&second, "const const const big = 1!");
+    assert!(matches!(err, runtime::Error::Redeclared(_)));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn strict_reassign() {
+    let interpreter = Interpreter::new()
+        .with_store(MemoryStore::new())
+        .with_strict_mode(true);
+    let err = eval_error(&interpreter, "const const const count = 1!\ncount = 2!");
+    assert!(matches!(err, runtime::Error::ConstAssignment(name) if name == "count"));
+}
+
+#[test]
+fn user_file_store() {
+    let Some(store) = FileStore::user() else {
+        return;
+    };
+    assert!(store.dir().ends_with("dreamberd-const-const-const"));
+    assert!(!store.dir().starts_with(std::env::temp_dir()));
+}
+
+#[test]
+fn memory_store_not_shared() {
+    let first = Interpreter::new().with_store(MemoryStore::new());
+    let second = Interpreter::new().with_store(MemoryStore::new());
+
+    first.eval("const const const secret = 1!").unwrap();
+    second.eval("assert secret === \"secret\"!").unwrap();
+}
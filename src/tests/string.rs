@@ -0,0 +1,63 @@
+use super::interpreter_test_output;
+
+#[test]
+fn escapes() {
+    let code = r#"
+print "a\tb\x41\u0042\u{43}"!
+print "\\n is not a new line"!
+print 'it\'s \"quoted\"'!
+print "unknown \q escape"!
+"#;
+    interpreter_test_output(
+        code,
+        "a\tbABC\n\\n is not a new line\nit's \"quoted\"\nunknown q escape\n",
+    );
+}
+
+#[test]
+fn escaped_backslash_at_end() {
+    let code = r#"
+const const path = "C:\\"!
+print path!
+"#;
+    interpreter_test_output(code, "C:\\\n");
+}
+
+#[test]
+fn unicode_escapes() {
+    let code = r#"
+print "\u{1F600} \uD83D\uDE00 \uD83D"!
+"#;
+    interpreter_test_output(code, "😀 😀 \u{FFFD}\n");
+}
+
+#[test]
+fn line_continuation() {
+    let code = "print \"one \\\ntwo\"!";
+    interpreter_test_output(code, "one two\n");
+}
+
+#[test]
+fn length() {
+    let code = r#"
+const const ascii = "hello"!
+const const emoji = "a😀"!
+print ascii.length!
+print emoji.length!
+"#;
+    interpreter_test_output(code, "5\n3\n");
+}
+
+#[test]
+fn bracket_indexing() {
+    let code = r#"
+const const text = "h€😀"!
+var var i = 1!
+print text[0]!
+print text[i]!
+print text[2]!
+print text[4]!
+print text["01"]!
+"#;
+    interpreter_test_output(code, "h\n€\n\u{FFFD}\nundefined\nundefined\n");
+}
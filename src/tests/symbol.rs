@@ -0,0 +1,81 @@
+use crate::{runtime, Interpreter};
+
+use super::{eval_error, interpreter_test_output};
+
+#[test]
+fn symbols_are_unique() {
+    let code = r#"
+const const a = Symbol "name"!
+const const b = Symbol "name"!
+assert a === a!
+assert a === b === false!
+var var type = typeof a!
+assert type === "symbol"!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
+
+#[test]
+fn print_symbol() {
+    interpreter_test_output("print Symbol \"name\"!", "Symbol(name)\n");
+}
+
+#[test]
+fn symbol_property_keys() {
+    let code = r#"
+const const key = Symbol "a"!
+var var obj = { a: 1 }!
+obj[key] = 2!
+assert obj[key] === 2!
+assert obj["a"] === 1!
+assert obj.a === 1!
+
+delete obj[key]!
+assert obj[key] === undefined!
+assert obj.a === 1!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
+
+#[test]
+fn well_known_symbols() {
+    let code = r#"
+const const toPrimitive = Symbol.toPrimitive!
+assert toPrimitive === Symbol.toPrimitive!
+assert toPrimitive === Symbol.iterator === false!
+"#;
+    Interpreter::new_eval(code).unwrap();
+    interpreter_test_output("print Symbol.iterator!", "Symbol(Symbol.iterator)\n");
+}
+
+#[test]
+fn well_known_symbols_are_shared() {
+    let interpreter = Interpreter::new();
+    let other = Interpreter::new();
+    let [first] = &interpreter.eval_repl("Symbol.iterator").unwrap()[..] else {
+        panic!("expected one value");
+    };
+    let [second] = &other.eval_repl("Symbol.iterator").unwrap()[..] else {
+        panic!("expected one value");
+    };
+    assert!(first.strict_eq(second));
+}
+
+#[test]
+fn symbol_without_description() {
+    let code = r#"
+var var s = Symbol()!
+var var type = typeof s!
+assert type === "symbol"!
+var var t = Symbol!
+assert s === t === false!
+"#;
+    Interpreter::new_eval(code).unwrap();
+    interpreter_test_output("print Symbol()!", "Symbol()\n");
+}
+
+#[test]
+fn increment_symbol() {
+    let err = eval_error(&Interpreter::new(), "var var s = Symbol(\"a\")!\ns++!");
+    assert!(matches!(err, runtime::Error::Type(_)));
+}
@@ -1,4 +1,6 @@
-use crate::Interpreter;
+use crate::{Error, Interpreter, ParseErrorKind};
+
+use super::interpreter_test_output;
 
 #[test]
 fn multiple_end() {
@@ -6,31 +8,31 @@ fn multiple_end() {
     Interpreter::new_eval(code).unwrap();
 }
 
-// TODO: reimplement those
-// #[test]
-// fn hello_world() {
-//     interpreter_test_output("print \"Hello, World!\"!", "Hello, World!\n");
-// }
-//
-// #[test]
-// fn string_no_quotes_no_space() {
-//     interpreter_test_output("print hello_world!", "hello_world\n");
-// }
-//
-// #[test]
-// fn string_no_quotes_spaces() {
-//     interpreter_test_output("print hello world!", "hello world\n");
-// }
+#[test]
+fn hello_world() {
+    interpreter_test_output("print \"Hello, World!\"!", "Hello, World!\n");
+}
 
-// #[test]
-// fn string_many_quotes() {
-//     let code = r#"
-// print """"""hello world""""""!
-// print '''''hello world'''''!
-// "#;
-//     interpreter_test_output(code, "hello world\nhello world\n");
-// }
+#[test]
+fn string_no_quotes_no_space() {
+    interpreter_test_output("print hello_world!", "hello_world\n");
+}
+
+#[test]
+fn string_no_quotes_spaces() {
+    interpreter_test_output("print hello world!", "hello world\n");
+}
 
+#[test]
+fn string_many_quotes() {
+    let code = r#"
+print """"""hello world""""""!
+print '''''hello world'''''!
+"#;
+    interpreter_test_output(code, "hello world\nhello world\n");
+}
+
+// TODO: mixed quotes aren't parsed as a string yet
 // #[test]
 // fn string_mixed_quotes() {
 //     let code = r#"
@@ -40,6 +42,7 @@ fn multiple_end() {
 //     interpreter_test_output(code, "hello world\"\nhello world\'\n");
 // }
 
+// TODO: unmatched quotes aren't parsed as a string yet
 // #[test]
 // fn string_single_quote() {
 //     let code = r#"
@@ -49,11 +52,11 @@ fn multiple_end() {
 //     interpreter_test_output(code, "\'hello world!\nhello world\"\n");
 // }
 
-// #[test]
-// fn string_escape_chars() {
-//     let code = r#"print "\' \" \\ \n"!"#;
-//     interpreter_test_output(code, "\' \" \\ \n\n");
-// }
+#[test]
+fn string_escape_chars() {
+    let code = r#"print "\' \" \\ \n"!"#;
+    interpreter_test_output(code, "\' \" \\ \n\n");
+}
 
 #[test]
 fn function_missing_order() {
@@ -65,32 +68,170 @@ fnto d() => 4!
     Interpreter::new_eval(code).unwrap();
 }
 
-// #[test]
-// fn comment_line() {
-//     let code = r#"
-// // This is a comment
-// print "Hello, World!"! // another comment
-// var var foo = // comment
-//     1!
-// print foo!
-// var var foo // comment
-//     = 2!
-// print foo!
-// "#;
-//     interpreter_test_output(code, "Hello, World!\n1\n2\n");
-// }
+#[test]
+fn comment_line() {
+    let code = r#"
+// This is a comment
+print "Hello, World!"! // another comment
+var var foo = // comment
+    1!
+print foo!
+var var foo // comment
+    = 2!
+print foo!
+"#;
+    interpreter_test_output(code, "Hello, World!\n1\n2\n");
+}
 
-// #[test]
-// fn comment_block() {
-//     let code = r#"
-// /* This is a comment
-//     that spans multiple lines */
-// print "Hello, World!"!
-// var var foo = /* comment */ 1!
-// print foo!
-// var var foo /* comment
-//     that spans multiple lines */ = 2!
-// print foo!
-// "#;
-//     interpreter_test_output(code, "Hello, World!\n1\n2\n");
-// }
+#[test]
+fn comment_block() {
+    let code = r#"
+/* This is a comment
+    that spans multiple lines */
+print "Hello, World!"!
+var var foo = /* comment */ 1!
+print foo!
+var var foo /* comment
+    that spans multiple lines */ = 2!
+print foo!
+"#;
+    interpreter_test_output(code, "Hello, World!\n1\n2\n");
+}
+
+/// Evaluates with parse diagnostics and gets the reported problem
+fn parse_error(code: &str) -> (ParseErrorKind, usize, usize, String) {
+    let interpreter = Interpreter::new().with_parse_diagnostics(true);
+    match interpreter.eval(code) {
+        Err(Error::Parse {
+            kind,
+            line,
+            column,
+            snippet,
+        }) => (kind, line, column, snippet),
+        res => panic!("expected parse error, got {res:?}"),
+    }
+}
+
+#[test]
+fn diagnostics_valid() {
+    let code = r#"
+// comment with "quote
+/* block { comment */
+function foo => {
+    print """hello world"""!
+    print 'it''s'!
+}
+var var bar = { a: "}" }!
+print don't!
+print "nested ${"quotes {"}"!
+"#;
+    let interpreter = Interpreter::new().with_parse_diagnostics(true);
+    interpreter.eval(code).unwrap();
+}
+
+#[test]
+fn diagnostics_unclosed_scope() {
+    let code = r#"
+function foo => {
+    {
+        print 1!
+    }
+"#;
+    let err = parse_error(code);
+    assert_eq!(
+        err,
+        (
+            ParseErrorKind::UnclosedScope,
+            2,
+            17,
+            "function foo => {".to_string()
+        )
+    );
+}
+
+#[test]
+fn diagnostics_unexpected_scope_end() {
+    let code = "print 1!
+  }
+";
+    let err = parse_error(code);
+    assert_eq!(
+        err,
+        (ParseErrorKind::UnexpectedScopeEnd, 2, 3, "  }".to_string())
+    );
+}
+
+#[test]
+fn diagnostics_unterminated_block_comment() {
+    let code = "print 1!
+print 2! /* comment
+print 3!";
+    let (kind, line, column, _) = parse_error(code);
+    assert_eq!(
+        (kind, line, column),
+        (ParseErrorKind::UnterminatedBlockComment, 2, 10)
+    );
+}
+
+#[test]
+fn diagnostics_mismatched_quotes() {
+    let code = r#"print ""hello world"'!"#;
+    let (kind, line, column, _) = parse_error(code);
+    assert_eq!(
+        (kind, line, column),
+        (ParseErrorKind::MismatchedQuotes, 1, 7)
+    );
+}
+
+#[test]
+fn diagnostics_unterminated_string() {
+    let code = "print 'hello world!";
+    let (kind, line, column, _) = parse_error(code);
+    assert_eq!(
+        (kind, line, column),
+        (ParseErrorKind::UnterminatedString, 1, 7)
+    );
+}
+
+#[test]
+fn diagnostics_disabled() {
+    let code = "print 'hello world!";
+    let io = crate::runtime::io::BufferIo::new();
+    Interpreter::new().with_io(io.clone()).eval(code).unwrap();
+    assert_eq!(io.output(), "'hello world\n");
+}
+
+#[test]
+fn unparsed_statement() {
+    let code = "print 1!
+/* comment
+print 2!";
+    let io = crate::runtime::io::BufferIo::new();
+    let err = Interpreter::new().with_io(io.clone()).eval(code);
+    match err {
+        Err(Error::Parse {
+            kind,
+            line,
+            column,
+            snippet,
+        }) => assert_eq!(
+            (kind, line, column, snippet),
+            (
+                ParseErrorKind::UnterminatedBlockComment,
+                2,
+                1,
+                "/* comment".to_string()
+            )
+        ),
+        res => panic!("expected parse error, got {res:?}"),
+    }
+    assert_eq!(io.output(), "1\n");
+}
+
+#[test]
+fn trailing_comment() {
+    let code = "print 1!
+// comment
+/* block */  ";
+    Interpreter::new().eval(code).unwrap();
+}
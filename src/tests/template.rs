@@ -0,0 +1,96 @@
+use crate::{
+    runtime::{io::BufferIo, locale::Locale},
+    Interpreter,
+};
+
+use super::interpreter_test_output;
+
+/// Evaluates the code with the locale and checks everything printed
+fn locale_test_output(locale: Locale, code: &str, expected: &str) {
+    let io = BufferIo::new();
+    let interpreter = Interpreter::new().with_io(io.clone()).with_locale(locale);
+    interpreter.eval(code).unwrap();
+    assert_eq!(io.output(), expected);
+}
+
+#[test]
+fn interpolation() {
+    let code = r#"
+const const name = "world"!
+print "Hello ${name}!"!
+print 'a ${1 + 2} b ${name + 1}'!
+"#;
+    interpreter_test_output(code, "Hello world!\na 3 b world1\n");
+}
+
+#[test]
+fn other_currencies_are_text() {
+    let code = r#"
+const const name = "world"!
+print "Hello £{name}, {name}€"!
+print "price: $5"!
+"#;
+    interpreter_test_output(code, "Hello £{name}, {name}€\nprice: $5\n");
+}
+
+#[test]
+fn locale_currency() {
+    let code = r#"
+const const name = "world"!
+print "Hello £{name} ${name}"!
+"#;
+    locale_test_output(
+        Locale::from_tag("en-GB").unwrap(),
+        code,
+        "Hello world ${name}\n",
+    );
+}
+
+#[test]
+fn locale_currency_suffix() {
+    let code = r#"
+const const name = "world"!
+print "Hello {name}€ €{name}"!
+"#;
+    locale_test_output(
+        Locale::from_tag("de-DE").unwrap(),
+        code,
+        "Hello world €{name}\n",
+    );
+    locale_test_output(
+        Locale::from_tag("pt-CV").unwrap(),
+        "const const name = \"world\"!\nprint \"Hello {name}$\"!",
+        "Hello world\n",
+    );
+}
+
+#[test]
+fn nested_quotes() {
+    let code = r#"
+const const name = "world"!
+print "a ${"b" + "c"} d"!
+print "outer ${"inner ${name}"}"!
+"#;
+    interpreter_test_output(code, "a bc d\nouter inner world\n");
+}
+
+#[test]
+fn escaped_currency() {
+    let code = r#"
+const const name = "world"!
+print "costs \$5 for \${name} and ${name}"!
+print "${"\$"}{name}"!
+"#;
+    interpreter_test_output(code, "costs $5 for ${name} and world\n${name}\n");
+}
+
+#[test]
+fn to_string() {
+    let code = r#"
+var var money = { cents: 5 }!
+money.toString = => "five"!
+money.valueOf = => 5!
+print "${money}"!
+"#;
+    interpreter_test_output(code, "five\n");
+}
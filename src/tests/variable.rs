@@ -1,4 +1,5 @@
-use crate::Interpreter;
+use super::{eval_error, interpreter_test_output};
+use crate::{runtime, Interpreter};
 
 #[test]
 fn declare() {
@@ -83,3 +84,220 @@ assert("a" === "b")!
 "#;
     Interpreter::new_eval(code).unwrap();
 }
+
+#[test]
+fn previous_steps() {
+    let code = r#"var var a = 1!
+a = 2!
+a = 3!
+assert(current a === 3)!
+assert(previous a === 2)!
+assert(previous previous a === 1)!
+assert(previous previous previous a === undefined)!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
+
+#[test]
+fn previous_history_len() {
+    let code = r#"var var a = 1!
+a = 2!
+a = 3!
+assert(previous a === 2)!
+assert(previous previous a === undefined)!
+"#;
+    Interpreter::new().with_history_len(1).eval(code).unwrap();
+}
+
+#[test]
+fn previous_property() {
+    let code = r#"const var obj = { a: 1 }!
+obj.a = 2!
+obj["a"] = 3!
+assert(previous obj.a === 2)!
+assert(previous previous obj["a"] === 1)!
+assert(current obj.a === 3)!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
+
+#[test]
+fn next_waits_for_assignment() {
+    let code = r#"var var a = 1!
+print(next a)!
+print("before")!
+a = 2!
+a = 3!
+"#;
+    interpreter_test_output(code, "before\n2\n");
+}
+
+#[test]
+fn next_declares_on_assignment() {
+    let code = r#"var var a = 1!
+const var b = next a!
+a = 2!
+assert(b === 2)!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
+
+#[test]
+fn next_assigned_in_function() {
+    let code = r#"var var a = 1!
+const var b = next a!
+function setA() => {
+   a = 2!
+}!
+setA()!
+assert b === 2!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
+
+#[test]
+fn next_waits_in_function() {
+    let code = r#"var var a = 1!
+function wait() => {
+   print(next a)!
+}!
+wait()!
+print("before")!
+a = 2!
+"#;
+    interpreter_test_output(code, "before\n2\n");
+}
+
+#[test]
+fn time_keyword_as_variable() {
+    let code = r#"const var next = 5!
+const var current = 6!
+assert(next === 5)!
+assert(current === 6)!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
+
+#[test]
+fn destructure_array() {
+    let code = r#"
+const const values = [1, 2]!
+const var [first, second, third] = values!
+assert first === 1!
+assert second === 2!
+assert third === undefined!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
+
+#[test]
+fn constants_ignore_writes() {
+    let code = r#"
+const const a = 1!
+a = 2!
+const const obj = { a: 1 }!
+obj.a = 2!
+assert a === 1!
+assert obj.a === 1!
+"#;
+    Interpreter::new_eval(code).unwrap();
+}
+
+#[test]
+fn strict_reassign() {
+    let err = eval_error(
+        &Interpreter::new().with_strict_mode(true),
+        "const const a = 1!\na = 2!",
+    );
+    assert!(matches!(err, runtime::Error::ConstAssignment(name) if name == "a"));
+    let err = eval_error(
+        &Interpreter::new().with_strict_mode(true),
+        "const var b = 1!\nb = 2!",
+    );
+    assert!(matches!(err, runtime::Error::ConstAssignment(name) if name == "b"));
+}
+
+#[test]
+fn strict_mutate() {
+    let err = eval_error(
+        &Interpreter::new().with_strict_mode(true),
+        "const const obj = { a: 1 }!\nobj.a = 2!",
+    );
+    assert!(matches!(err, runtime::Error::ConstMutation(name) if name == "obj"));
+    let err = eval_error(
+        &Interpreter::new().with_strict_mode(true),
+        "var const obj = { a: 1 }!\nobj.a = 2!",
+    );
+    assert!(matches!(err, runtime::Error::ConstMutation(name) if name == "obj"));
+}
+
+#[test]
+fn strict_compound() {
+    let err = eval_error(
+        &Interpreter::new().with_strict_mode(true),
+        "const const n = 1!\nn += 1!",
+    );
+    assert!(matches!(err, runtime::Error::ConstAssignment(_)));
+    let err = eval_error(
+        &Interpreter::new().with_strict_mode(true),
+        "const var n = 1!\nn++!",
+    );
+    assert!(matches!(err, runtime::Error::ConstAssignment(_)));
+    let err = eval_error(
+        &Interpreter::new().with_strict_mode(true),
+        "var const obj = { a: 1 }!\nobj.a *= 2!",
+    );
+    assert!(matches!(err, runtime::Error::ConstMutation(_)));
+}
+
+#[test]
+fn strict_array_methods() {
+    for method in [
+        "push(3)",
+        "pop()",
+        "shift()",
+        "unshift(0)",
+        "splice(-1, 1, undefined)",
+        "reverse()",
+    ] {
+        let err = eval_error(
+            &Interpreter::new().with_strict_mode(true),
+            &format!("const const arr = [1, 2]!\narr.{method}!"),
+        );
+        assert!(
+            matches!(&err, runtime::Error::ConstMutation(name) if name == "arr"),
+            "{method} didn't fail with ConstMutation: {err:?}"
+        );
+    }
+    let err = eval_error(
+        &Interpreter::new().with_strict_mode(true),
+        "var const arr = [1, 2]!\narr.push(3)!",
+    );
+    assert!(matches!(err, runtime::Error::ConstMutation(_)));
+
+    let interpreter = Interpreter::new().with_strict_mode(true);
+    interpreter
+        .eval("const var arr = [1, 2]!\narr.push(3)!\nassert arr.length === 3!")
+        .unwrap();
+}
+
+#[test]
+fn strict_allowed() {
+    let code = r#"
+var var a = 1!
+a = 2!
+a += 1!
+var const b = 1!
+b = 2!
+const var obj = { a: 1 }!
+obj.a = 2!
+undeclared = 3!
+assert a === 3!
+assert b === 2!
+assert obj.a === 2!
+"#;
+    Interpreter::new()
+        .with_strict_mode(true)
+        .eval(code)
+        .unwrap();
+}